@@ -0,0 +1,354 @@
+//! Balance analytics over the game's telemetry (see game/src/telemetry.rs and the
+//! telemetry-schema crate, which is all this needs from the game).
+//! Reads one or more telemetry files and prints:
+//! class win rates, a class-vs-class matchup table, average damage per hit,
+//! parry success rates and time-to-kill distributions.
+//!
+//! cargo run --package analytics -- [telemetry files...]
+//! with no files it reads telemetry/events.jsonl
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufRead, BufReader},
+};
+use telemetry_schema::{PlayerInfo, TelemetryEvent, TelemetryLine, TICK_RATE};
+
+//everything recorded about one match
+#[derive(Default)]
+struct Match {
+    players: Vec<PlayerInfo>,
+    //slot of the winner, if the match was decided and somebody won
+    winner: Option<usize>,
+    completed: bool,
+    events: Vec<(u32, TelemetryEvent)>,
+}
+
+//each report is worked out by one function and printed by another
+//class -> a pair of counts, e.g. (played, won)
+type Table = BTreeMap<String, (u32, u32)>;
+
+//upper edges of the time-to-kill histogram buckets, in seconds
+const TTK_BUCKETS: [f32; 5] = [5.0, 10.0, 20.0, 40.0, 80.0];
+
+fn main() {
+    let mut paths: Vec<String> = std::env::args().skip(1).collect();
+    if paths.is_empty() {
+        paths.push("telemetry/events.jsonl".to_string());
+    }
+
+    let matches = read_matches(&paths);
+    let decided = matches.values().filter(|m| m.completed).count();
+    println!("{} matches ({} decided) from {}", matches.len(), decided, paths.join(", "));
+
+    print_win_rates(&win_rates(&matches));
+    print_matchups(&matchups(&matches));
+    print_damage_per_hit(&damage_per_hit(&matches));
+    print_parry_rates(&parry_rates(&matches));
+    print_time_to_kill(&time_to_kill(&matches));
+}
+
+//every match in the files, by match id. lines that don't parse are skipped and counted
+fn read_matches(paths: &[String]) -> BTreeMap<u64, Match> {
+    let mut matches: BTreeMap<u64, Match> = BTreeMap::new();
+    let mut skipped = 0;
+    for path in paths {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("couldn't open {path}: {e}");
+                continue;
+            },
+        };
+        for line in BufReader::new(file).lines() {
+            let line = match line.map(|l| serde_json::from_str::<TelemetryLine>(&l)) {
+                Ok(Ok(line)) => line,
+                _ => {
+                    skipped += 1;
+                    continue;
+                },
+            };
+            let game = matches.entry(line.match_id).or_default();
+            match &line.event {
+                TelemetryEvent::MatchStarted{players, ..} => game.players = players.clone(),
+                TelemetryEvent::MatchEnded{winner, completed, ..} => {
+                    game.winner = winner.as_ref().map(|w| w.slot);
+                    game.completed = *completed;
+                },
+                _ => (),
+            }
+            game.events.push((line.tick, line.event));
+        }
+    }
+    if skipped > 0 {
+        eprintln!("skipped {skipped} lines that weren't telemetry");
+    }
+    matches
+}
+
+fn class(player: &PlayerInfo) -> String {
+    player.class.clone()
+}
+
+fn percent(part: u32, whole: u32) -> String {
+    if whole == 0 {
+        "-".to_string()
+    } else {
+        format!("{:.0}%", part as f32 * 100.0 / whole as f32)
+    }
+}
+
+fn win_rates(matches: &BTreeMap<u64, Match>) -> Table {
+    //class -> (matches played, matches won)
+    let mut rates = Table::new();
+    for game in matches.values().filter(|m| m.completed) {
+        for player in &game.players {
+            let entry = rates.entry(class(player)).or_default();
+            entry.0 += 1;
+            if game.winner == Some(player.slot) {
+                entry.1 += 1;
+            }
+        }
+    }
+    rates
+}
+
+fn print_win_rates(rates: &Table) {
+    println!("\nwin rates (decided matches)");
+    println!("{:<10} {:>7} {:>5} {:>6}", "class", "played", "won", "rate");
+    for (class, (played, won)) in rates {
+        println!("{:<10} {:>7} {:>5} {:>6}", class, played, won, percent(*won, *played));
+    }
+}
+
+fn matchups(matches: &BTreeMap<u64, Match>) -> BTreeMap<(String, String), (u32, u32)> {
+    //(class, opponent class) -> (matches against each other, matches class won)
+    let mut table: BTreeMap<(String, String), (u32, u32)> = BTreeMap::new();
+    for game in matches.values().filter(|m| m.completed) {
+        for a in &game.players {
+            for b in game.players.iter().filter(|b| b.slot != a.slot) {
+                let entry = table.entry((class(a), class(b))).or_default();
+                entry.0 += 1;
+                if game.winner == Some(a.slot) {
+                    entry.1 += 1;
+                }
+            }
+        }
+    }
+    table
+}
+
+fn print_matchups(table: &BTreeMap<(String, String), (u32, u32)>) {
+    let mut classes: Vec<String> = table.keys().map(|(row, _)| row.clone()).collect();
+    classes.dedup();
+
+    println!("\nmatchups: how often the row class beat the column class (matches)");
+    print!("{:<10}", "");
+    for column in &classes {
+        print!(" {:>12}", column);
+    }
+    println!();
+    for row in &classes {
+        print!("{:<10}", row);
+        for column in &classes {
+            let (played, won) = table.get(&(row.clone(), column.clone())).cloned().unwrap_or_default();
+            print!(" {:>12}", format!("{} ({})", percent(won, played), played));
+        }
+        println!();
+    }
+}
+
+fn damage_per_hit(matches: &BTreeMap<u64, Match>) -> Table {
+    //attacking class -> (hits, total damage)
+    let mut damage = Table::new();
+    for (_, event) in matches.values().flat_map(|m| &m.events) {
+        if let TelemetryEvent::Hit{attacker, damage: amount, ..} = event {
+            let attacker = attacker.as_ref().map_or("(none)".to_string(), class);
+            let entry = damage.entry(attacker).or_default();
+            entry.0 += 1;
+            entry.1 += amount;
+        }
+    }
+    damage
+}
+
+fn print_damage_per_hit(damage: &Table) {
+    println!("\ndamage per hit, by attacker");
+    println!("{:<10} {:>6} {:>7} {:>8}", "class", "hits", "damage", "average");
+    for (class, (hits, total)) in damage {
+        println!("{:<10} {:>6} {:>7} {:>8.2}", class, hits, total, *total as f32 / (*hits).max(1) as f32);
+    }
+}
+
+fn parry_rates(matches: &BTreeMap<u64, Match>) -> Table {
+    //class -> (parries started, parries that caught something)
+    let mut parries = Table::new();
+    for game in matches.values() {
+        //slots whose last parry hasn't caught anything yet; one parry can catch several hits,
+        // but it only counts as landed once
+        let mut open: Vec<usize> = Vec::new();
+        for (_, event) in &game.events {
+            match event {
+                TelemetryEvent::ParryStarted{player} => {
+                    parries.entry(class(player)).or_default().0 += 1;
+                    if !open.contains(&player.slot) {
+                        open.push(player.slot);
+                    }
+                },
+                TelemetryEvent::Parry{player, ..} if open.contains(&player.slot) => {
+                    parries.entry(class(player)).or_default().1 += 1;
+                    open.retain(|slot| *slot != player.slot);
+                },
+                _ => (),
+            }
+        }
+    }
+    parries
+}
+
+fn print_parry_rates(parries: &Table) {
+    println!("\nparry success");
+    println!("{:<10} {:>8} {:>7} {:>6}", "class", "started", "landed", "rate");
+    for (class, (started, landed)) in parries {
+        println!("{:<10} {:>8} {:>7} {:>6}", class, started, landed, percent(*landed, *started));
+    }
+}
+
+//killer class -> seconds from the victim (re)spawning to dying, shortest first
+fn time_to_kill(matches: &BTreeMap<u64, Match>) -> BTreeMap<String, Vec<f32>> {
+    let mut times: BTreeMap<String, Vec<f32>> = BTreeMap::new();
+    for game in matches.values() {
+        //slot -> tick they last came into the match
+        let mut alive_since: BTreeMap<usize, u32> = game.players.iter().map(|p| (p.slot, 0)).collect();
+        for (tick, event) in &game.events {
+            match event {
+                TelemetryEvent::Respawn{player, ..} => {alive_since.insert(player.slot, *tick);},
+                TelemetryEvent::Death{player, killer: Some(killer), ..} if killer.slot != player.slot => {
+                    let since = alive_since.get(&player.slot).cloned().unwrap_or(0);
+                    let seconds = tick.saturating_sub(since) as f32 / TICK_RATE as f32;
+                    times.entry(class(killer)).or_default().push(seconds);
+                },
+                _ => (),
+            }
+        }
+    }
+    for times in times.values_mut() {
+        times.sort_by(|a, b| a.total_cmp(b));
+    }
+    times
+}
+
+//the value `fraction` of the way through some sorted, non-empty times
+fn percentile(times: &[f32], fraction: f32) -> f32 {
+    times[((times.len() - 1) as f32 * fraction).round() as usize]
+}
+
+fn print_time_to_kill(times: &BTreeMap<String, Vec<f32>>) {
+    println!("\ntime to kill in seconds, by killer (from the victim spawning)");
+    print!("{:<10} {:>6} {:>7} {:>7} {:>7}", "class", "kills", "median", "mean", "p90");
+    let mut low = 0.0;
+    for high in TTK_BUCKETS {
+        print!(" {:>7}", format!("{low}-{high}"));
+        low = high;
+    }
+    println!(" {:>7}", format!("{low}+"));
+
+    for (class, times) in times {
+        let mean = times.iter().sum::<f32>() / times.len() as f32;
+        print!("{:<10} {:>6} {:>7.1} {:>7.1} {:>7.1}", class, times.len(), percentile(times, 0.5), mean, percentile(times, 0.9));
+        let mut low = 0.0;
+        for high in TTK_BUCKETS {
+            print!(" {:>7}", times.iter().filter(|t| **t >= low && **t < high).count());
+            low = high;
+        }
+        println!(" {:>7}", times.iter().filter(|t| **t >= low).count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(slot: usize, class: &str) -> PlayerInfo {
+        PlayerInfo{slot, class: class.to_string(), bot: false}
+    }
+
+    fn matches(games: Vec<Match>) -> BTreeMap<u64, Match> {
+        games.into_iter().enumerate().map(|(id, game)| (id as u64, game)).collect()
+    }
+
+    //a barbarian (slot 0) against a rogue (slot 1)
+    fn duel(winner: Option<usize>, completed: bool, events: Vec<(u32, TelemetryEvent)>) -> Match {
+        Match{players: vec![player(0, "Barbarian"), player(1, "Rogue")], winner, completed, events}
+    }
+
+    #[test]
+    fn win_rates_only_count_decided_matches() {
+        let rates = win_rates(&matches(vec![
+            duel(Some(0), true, Vec::new()),
+            duel(Some(1), true, Vec::new()),
+            duel(Some(0), true, Vec::new()),
+            //the game was closed halfway through
+            duel(None, false, Vec::new()),
+        ]));
+        assert_eq!(rates["Barbarian"], (3, 2));
+        assert_eq!(rates["Rogue"], (3, 1));
+    }
+
+    #[test]
+    fn matchups_count_both_ways() {
+        let table = matchups(&matches(vec![duel(Some(0), true, Vec::new()), duel(Some(0), true, Vec::new())]));
+        assert_eq!(table[&("Barbarian".to_string(), "Rogue".to_string())], (2, 2));
+        assert_eq!(table[&("Rogue".to_string(), "Barbarian".to_string())], (2, 0));
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn damage_is_grouped_by_attacker() {
+        let hit = |attacker: Option<PlayerInfo>, damage| TelemetryEvent::Hit{attacker, victim: player(1, "Rogue"), damage, position: [0.0, 0.0]};
+        let damage = damage_per_hit(&matches(vec![duel(None, false, vec![
+            (10, hit(Some(player(0, "Barbarian")), 4)),
+            (20, hit(Some(player(0, "Barbarian")), 2)),
+            (30, hit(None, 1)),
+        ])]));
+        assert_eq!(damage["Barbarian"], (2, 6));
+        assert_eq!(damage["(none)"], (1, 1));
+    }
+
+    #[test]
+    fn a_parry_catching_several_hits_lands_once() {
+        let started = TelemetryEvent::ParryStarted{player: player(1, "Rogue")};
+        let parried = || TelemetryEvent::Parry{player: player(1, "Rogue"), attacker: Some(player(0, "Barbarian")), position: [0.0, 0.0]};
+        let parries = parry_rates(&matches(vec![duel(None, false, vec![
+            (10, started.clone()),
+            (12, parried()),
+            (13, parried()),
+            (14, parried()),
+            //a second parry that whiffs
+            (40, started),
+        ])]));
+        assert_eq!(parries["Rogue"], (2, 1));
+    }
+
+    #[test]
+    fn time_to_kill_runs_from_the_last_respawn() {
+        let rate = TICK_RATE as u32;
+        let death = |player: PlayerInfo, killer: PlayerInfo| TelemetryEvent::Death{player, killer: Some(killer), position: [0.0, 0.0]};
+        let times = time_to_kill(&matches(vec![duel(None, false, vec![
+            (10 * rate, death(player(1, "Rogue"), player(0, "Barbarian"))),
+            (15 * rate, TelemetryEvent::Respawn{player: player(1, "Rogue"), position: [0.0, 0.0]}),
+            (18 * rate, death(player(1, "Rogue"), player(0, "Barbarian"))),
+            //killing yourself isn't a kill
+            (20 * rate, death(player(0, "Barbarian"), player(0, "Barbarian"))),
+        ])]));
+        assert_eq!(times["Barbarian"], vec![3.0, 10.0]);
+        assert_eq!(times.len(), 1);
+    }
+
+    #[test]
+    fn percentiles_of_one_sample_are_that_sample() {
+        assert_eq!(percentile(&[4.0], 0.5), 4.0);
+        assert_eq!(percentile(&[4.0], 0.9), 4.0);
+        assert_eq!(percentile(&[1.0, 2.0, 3.0], 0.5), 2.0);
+        assert_eq!(percentile(&[1.0, 2.0, 3.0], 0.9), 3.0);
+    }
+}
@@ -12,6 +12,6 @@ fn main() {
             scene: "data/scene1.rgs".into(),
         }),
     );
-    editor.add_game_plugin(GameConstructor);
+    editor.add_game_plugin(GameConstructor::default());
     editor.run(event_loop)
 }
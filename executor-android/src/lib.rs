@@ -3,7 +3,7 @@ use fyrox::{
     core::io, engine::executor::Executor, event_loop::EventLoopBuilder,
     platform::android::EventLoopBuilderExtAndroid,
 };
-use ShapeFights::GameConstructor;
+use ShapeFights::{clock::TICK_RATE, GameConstructor};
 
 #[no_mangle]
 fn android_main(app: fyrox::platform::android::activity::AndroidApp) {
@@ -12,6 +12,8 @@ fn android_main(app: fyrox::platform::android::activity::AndroidApp) {
         .expect("ANDROID_APP cannot be set twice.");
     let event_loop = EventLoopBuilder::new().with_android_app(app).build();
     let mut executor = Executor::from_params(event_loop, Default::default());
-    executor.add_plugin_constructor(GameConstructor);
+    //one engine update per simulation tick
+    executor.set_desired_update_rate(TICK_RATE as f32);
+    executor.add_plugin_constructor(GameConstructor::default());
     executor.run()
 }
\ No newline at end of file
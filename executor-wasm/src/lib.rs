@@ -1,6 +1,6 @@
 //! Executor with your game connected to it as a plugin.
 use fyrox::engine::executor::Executor;
-use ShapeFights::GameConstructor;
+use ShapeFights::{clock::TICK_RATE, GameConstructor};
 use fyrox::core::wasm_bindgen::{self, prelude::*};
 
 #[wasm_bindgen]
@@ -40,6 +40,8 @@ pub fn set_panic_hook() {
 pub fn main() {
     set_panic_hook();
     let mut executor = Executor::new();
-    executor.add_plugin_constructor(GameConstructor);
+    //one engine update per simulation tick
+    executor.set_desired_update_rate(TICK_RATE as f32);
+    executor.add_plugin_constructor(GameConstructor::default());
     executor.run()
 }
\ No newline at end of file
@@ -1,9 +1,49 @@
 //! Executor with your game connected to it as a plugin.
-use fyrox::engine::executor::Executor;
-use ShapeFights::GameConstructor;
+//! Run with --help for the command line options (see game/src/launch.rs).
+use fyrox::{
+    dpi::LogicalSize,
+    engine::{executor::Executor, GraphicsContextParams},
+    event_loop::EventLoop,
+    window::{Fullscreen, WindowAttributes},
+};
+use ShapeFights::{clock::TICK_RATE, launch::LaunchOptions, options::Options, GameConstructor};
 
 fn main() {
-    let mut executor = Executor::new();
-    executor.add_plugin_constructor(GameConstructor);
+    let launch = match LaunchOptions::parse(std::env::args().skip(1)) {
+        Ok(launch) => launch,
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(2);
+        },
+    };
+    if launch.help {
+        println!("{}", LaunchOptions::USAGE);
+        return;
+    }
+
+    //the window starts the way the options file says, unless the command line says otherwise
+    let options = Options::load();
+    let mut window_attributes = WindowAttributes::default();
+    window_attributes.title = "ShapeFights".to_string();
+    window_attributes.resizable = true;
+    if let Some((width, height)) = launch.window_size {
+        window_attributes.inner_size = Some(LogicalSize::new(width, height).into());
+    }
+    if launch.fullscreen || options.fullscreen {
+        window_attributes.fullscreen = Some(Fullscreen::Borderless(None));
+    }
+
+    let mut executor = Executor::from_params(
+        EventLoop::new().unwrap(),
+        GraphicsContextParams {
+            window_attributes,
+            vsync: options.vsync,
+            msaa_sample_count: None,
+        },
+    );
+    executor.set_headless(launch.headless);
+    //one engine update per simulation tick
+    executor.set_desired_update_rate(TICK_RATE as f32);
+    executor.add_plugin_constructor(GameConstructor { launch });
     executor.run()
-}
\ No newline at end of file
+}
@@ -0,0 +1,210 @@
+//this module contains the game's audio:
+// per-class sound effects (swing, hit, parry, projectile, death, respawn)
+// menu and battle music
+// volume settings
+//most sound effects are played in response to GameEvents (see Audio::on_event); anything
+// without an event (weapon swings) is queued by scripts with play_sfx.
+//Game::update turns the queue into sound nodes.
+//sound files are optional: a path that isn't on disk is warned about once and then skipped,
+// so a build without the sound pack just plays silently.
+use crate::*;
+use fyrox::scene::sound::{Sound, SoundBuffer, SoundBufferResource, SoundBuilder, Status};
+use serde::{Deserialize, Serialize};
+
+///the sound effects for one class
+#[derive(Debug, Clone, Copy)]
+pub struct SoundSet {
+    pub swing: &'static str,
+    pub hit: &'static str,
+    pub parry: &'static str,
+    pub fire: &'static str,
+    pub death: &'static str,
+    pub respawn: &'static str,
+}
+
+impl Default for SoundSet {
+    fn default() -> Self {
+        Self {
+            swing: "data/sounds/swing.ogg",
+            hit: "data/sounds/hit.ogg",
+            parry: "data/sounds/parry.ogg",
+            fire: "data/sounds/fire.ogg",
+            death: "data/sounds/death.ogg",
+            respawn: "data/sounds/respawn.ogg",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Music {
+    Menu,
+    Battle,
+}
+
+impl Music {
+    pub fn path(&self) -> &'static str {
+        match self {
+            Music::Menu => "data/music/menu.ogg",
+            Music::Battle => "data/music/battle.ogg",
+        }
+    }
+}
+
+///volumes from 0.0 to 1.0; music and sfx are scaled by master
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VolumeSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for VolumeSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.5,
+            sfx: 0.8,
+        }
+    }
+}
+
+//a sound effect waiting to be played, with the x position it came from
+struct Cue {
+    sound: &'static str,
+    x: f32,
+}
+
+#[derive(Default)]
+pub struct Audio {
+    pub volume: VolumeSettings,
+    music: Option<(Music, Handle<Node>)>,
+    queue: Vec<Cue>,
+    //whether each sound file we've been asked for is on disk, so we only look once
+    available: HashMap<&'static str, bool>,
+}
+
+impl Audio {
+    //sounds at this distance from the center of the arena are panned all the way to one side
+    pub const ARENA_HALF_WIDTH: f32 = 8.0;
+
+    pub fn queue_sfx(&mut self, sound: &'static str, x: f32) {
+        self.queue.push(Cue{sound, x});
+    }
+
+    ///the buffer for a sound file, or None if the file isn't there
+    fn buffer(&mut self, path: &'static str, resource_manager: &ResourceManager) -> Option<SoundBufferResource> {
+        let available = *self.available.entry(path).or_insert_with(|| {
+            let exists = Path::new(path).exists();
+            if !exists {
+                warn!("sound file {path} is missing; it won't be played");
+            }
+            exists
+        });
+        available.then(|| resource_manager.request::<SoundBuffer, _>(path))
+    }
+
+    ///queues the sound effect for a game event, in the class sounds of the player it happened to
+    pub fn on_event(&mut self, event: &GameEvent, graph: &Graph) {
+        let sounds = |player: &Handle<Node>| graph.try_get(*player)
+            .and_then(|n| n.try_get_script::<Player>())
+            .map(|p| (p.class.behavior().sounds(), p.health));
+
+        match event {
+            GameEvent::PlayerDamaged{player, position, ..} => {
+                //dying plays its own sound
+                if let Some((sounds, health)) = sounds(player) {
+                    if health > 0 {
+                        self.queue_sfx(sounds.hit, position.x);
+                    }
+                }
+            },
+            GameEvent::PlayerDied{player, position, ..} => {
+                if let Some((sounds, _)) = sounds(player) {
+                    self.queue_sfx(sounds.death, position.x);
+                }
+            },
+            GameEvent::PlayerRespawned{player, position} => {
+                if let Some((sounds, _)) = sounds(player) {
+                    self.queue_sfx(sounds.respawn, position.x);
+                }
+            },
+            GameEvent::ParrySucceeded{player, position, ..} => {
+                if let Some((sounds, _)) = sounds(player) {
+                    self.queue_sfx(sounds.parry, position.x);
+                }
+            },
+            GameEvent::ProjectileFired{player, position, ..} => {
+                if let Some((sounds, _)) = sounds(player) {
+                    self.queue_sfx(sounds.fire, position.x);
+                }
+            },
+            GameEvent::ParryStarted{..} | GameEvent::PlayerChanged{..} => (),
+        }
+    }
+
+    ///plays every queued sound effect; called once per frame from Game::update
+    pub fn update(&mut self, graph: &mut Graph, resource_manager: &ResourceManager) {
+        let gain = self.volume.master * self.volume.sfx;
+        for cue in std::mem::take(&mut self.queue) {
+            let Some(buffer) = self.buffer(cue.sound, resource_manager) else {
+                continue;
+            };
+            let panning = (cue.x / Self::ARENA_HALF_WIDTH).clamp(-1.0, 1.0);
+            SoundBuilder::new(BaseBuilder::new())
+                .with_buffer(Some(buffer))
+                .with_status(Status::Playing)
+                .with_play_once(true)
+                .with_gain(gain)
+                .with_panning(panning)
+                .with_spatial_blend_factor(0.0)
+                .build(graph);
+        }
+    }
+
+    ///starts looping the given music, replacing whatever was playing
+    pub fn play_music(&mut self, music: Music, graph: &mut Graph, resource_manager: &ResourceManager) {
+        if let Some((current, _)) = self.music {
+            if current == music {
+                return;
+            }
+        }
+        self.stop_music(graph);
+
+        let Some(buffer) = self.buffer(music.path(), resource_manager) else {
+            return;
+        };
+        let handle = SoundBuilder::new(BaseBuilder::new())
+            .with_buffer(Some(buffer))
+            .with_status(Status::Playing)
+            .with_looping(true)
+            .with_gain(self.volume.master * self.volume.music)
+            .with_spatial_blend_factor(0.0)
+            .build(graph);
+        self.music = Some((music, handle));
+    }
+
+    pub fn stop_music(&mut self, graph: &mut Graph) {
+        if let Some((_, handle)) = self.music.take() {
+            if graph.is_valid_handle(handle) {
+                graph.remove_node(handle);
+            }
+        }
+    }
+
+    ///changes the volume, updating the music that's already playing
+    pub fn set_volume(&mut self, volume: VolumeSettings, graph: &mut Graph) {
+        self.volume = volume;
+        if let Some((_, handle)) = self.music {
+            if let Some(sound) = graph.try_get_mut(handle).and_then(|n| n.cast_mut::<Sound>()) {
+                sound.set_gain(volume.master * volume.music);
+            }
+        }
+    }
+}
+
+///queues a sound effect coming from the given x position in the arena
+pub fn play_sfx(plugins: &mut [Box<dyn Plugin>], sound: &'static str, x: f32) {
+    if let Some(game) = game_mut(plugins) {
+        game.audio.queue_sfx(sound, x);
+    }
+}
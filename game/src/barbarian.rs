@@ -0,0 +1,43 @@
+//the Barbarian: big, slow, and hits hard
+use crate::*;
+use crate::behavior::{ClassBehavior, MeleeStats};
+use fyrox::scene::dim2::collider::CuboidShape;
+
+pub struct BarbarianBehavior;
+
+impl ClassBehavior for BarbarianBehavior {
+    fn health(&self) -> u32 { Class::BARBHEALTH }
+    fn speed(&self) -> f32 { Class::BARBSPD }
+    fn sprite(&self) -> &'static str { "data/White_square.png" }
+    fn weapon_shape(&self) -> CuboidShape { Class::BARBWEP }
+    fn weapon_offset(&self) -> f32 { 1.0 }
+
+    fn feedback(&self) -> FeedbackTuning {
+        FeedbackTuning {
+            shake_threshold: 3,
+            shake_strength: 0.25,
+            ..Default::default()
+        }
+    }
+
+    fn melee(&self) -> MeleeStats {
+        MeleeStats {
+            frames: Class::BARBFRAMES,
+            swing_speed: Class::BARBWEPSPD,
+            damage: Class::BARBDAM,
+            knockback: Class::BARBKNOCK,
+        }
+    }
+
+    fn sounds(&self) -> SoundSet {
+        SoundSet {
+            swing: "data/sounds/barbarian_swing.ogg",
+            ..SoundSet::default()
+        }
+    }
+
+    ///the barbarian has no special yet, so the left trigger does nothing, same as before the
+    /// ClassBehavior split: the old start_charge was commented out where the trigger was handled.
+    /// the charge (CHARLEN, CCOOL and the Charging state) comes back here as its own gameplay change
+    fn special(&self, _player: &mut Player, _ctx: &mut ScriptMessageContext) {}
+}
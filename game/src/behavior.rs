@@ -0,0 +1,76 @@
+//this module contains the ClassBehavior trait, which holds everything that makes one class
+//play differently from another, and the table that maps each Class to its implementation.
+// the implementations themselves live in one file per class (barbarian.rs, rogue.rs, ...)
+// so adding or modding a class only means touching that file and registering it below.
+use crate::*;
+use fyrox::scene::dim2::collider::CuboidShape;
+
+use crate::barbarian::BarbarianBehavior;
+use crate::rogue::RogueBehavior;
+use crate::wizard::WizardBehavior;
+use crate::fighter::FighterBehavior;
+
+///the numbers that describe a class's basic melee swing
+#[derive(Debug, Clone, Copy)]
+pub struct MeleeStats {
+    pub frames: FrameData,
+    //number of radians the weapon moves per frame of the swing (startup and active)
+    pub swing_speed: f32,
+    pub damage: u32,
+    pub knockback: f32,
+}
+
+pub trait ClassBehavior: Sync {
+    //starting / max health
+    fn health(&self) -> u32;
+    //speed of normal movement
+    fn speed(&self) -> f32;
+    //path to the (white) sprite, colored in per team by create_player
+    fn sprite(&self) -> &'static str;
+    //shape of weapon (each number is half of the length of one of the sides)
+    fn weapon_shape(&self) -> CuboidShape;
+    //how far from the center of the player the weapon sits
+    fn weapon_offset(&self) -> f32 { 0.75 }
+    fn melee(&self) -> MeleeStats;
+    //frames the special needs to recharge, if it has a cooldown
+    fn special_cooldown(&self) -> Option<i32> { None }
+    //how hard this class's hits are felt: hit-stop, screen shake, flash
+    fn feedback(&self) -> FeedbackTuning { FeedbackTuning::default() }
+    //sound effects for this class's actions
+    fn sounds(&self) -> SoundSet { SoundSet::default() }
+
+    ///right trigger; by default every class swings its weapon
+    fn primary_attack(&self, player: &mut Player, ctx: &mut ScriptMessageContext) {
+        player.start_melee_attack(ctx);
+    }
+
+    ///left trigger
+    fn special(&self, player: &mut Player, ctx: &mut ScriptMessageContext);
+
+    ///called on the player whose parry just caught an attack
+    fn parry_response(&self, _player: &mut Player, _ctx: &mut ScriptMessageContext) {}
+
+    ///called by the weapon when it lands a hit on a player who can take damage
+    fn on_hit(&self, _weapon: &Weapon, _target: Handle<Node>, _ctx: &mut ScriptContext) {}
+
+    ///called on the player when a hit they landed killed someone
+    fn on_kill(&self, _player: &mut Player, _ctx: &mut ScriptMessageContext) {}
+
+    ///called every frame from Player::on_update, after the state has been advanced
+    fn tick(&self, _player: &mut Player, _ctx: &mut ScriptContext) {}
+}
+
+//one entry per Class variant, in the same order as the enum
+static CLASS_TABLE: [&dyn ClassBehavior; 4] = [
+    &BarbarianBehavior,
+    &RogueBehavior,
+    &WizardBehavior,
+    &FighterBehavior,
+];
+
+impl Class {
+    ///looks up the behavior for this class
+    pub fn behavior(&self) -> &'static dyn ClassBehavior {
+        CLASS_TABLE[self.clone() as usize]
+    }
+}
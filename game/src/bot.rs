@@ -0,0 +1,328 @@
+//this module contains the CPU-controlled players that fill empty slots.
+//a bot only ever sends the PadInputs a gamepad would (through Game::send_input, so they're
+// recorded in replays like anyone else's), and only looks at the scene every so often:
+// how often depends on its difficulty, which is its reaction time
+//every time it looks it picks the nearest living enemy and
+// sidesteps any projectile about to hit it
+// parries if the enemy is swinging at it (if it notices, which also depends on difficulty)
+// otherwise plays its class: barbarians, rogues and fighters close in and swing (fighters shoot
+//  their charges from range), and wizards keep their distance and shoot
+//each bot has its own rng seeded from the match seed, so bots don't knock the match rng out of step
+// with a replay (which plays the bots' recorded inputs back without running them)
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    ///ticks between looks at the scene
+    pub fn reaction(&self) -> u32 {
+        match self {
+            Difficulty::Easy => (TICK_RATE / 2) as u32,
+            Difficulty::Medium => (TICK_RATE / 4) as u32,
+            Difficulty::Hard => (TICK_RATE / 10) as u32,
+        }
+    }
+
+    ///how likely the bot is to parry a swing it sees coming
+    pub fn parry_chance(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.1,
+            Difficulty::Medium => 0.4,
+            Difficulty::Hard => 0.8,
+        }
+    }
+
+    ///how likely the bot is to take a swing when it's in range
+    pub fn aggression(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.3,
+            Difficulty::Medium => 0.6,
+            Difficulty::Hard => 0.9,
+        }
+    }
+
+    ///the next difficulty, wrapping around; for the menu
+    pub fn next(&self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Medium,
+            Difficulty::Medium => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+}
+
+///what the bot learned from one look at the scene
+struct Sight {
+    position: Vector2<f32>,
+    enemy: Option<(Handle<Node>, Vector2<f32>, PlayerState)>,
+    //the nearest projectile that will pass close to us, and which way it's going
+    incoming: Option<Vector2<f32>>,
+}
+
+pub struct Bot {
+    pub slot: usize,
+    pub difficulty: Difficulty,
+    //ticks until the bot looks at the scene again
+    wait: u32,
+    //the enemy whose swing we've already decided whether to parry
+    seen_attack: Option<Handle<Node>>,
+    //which way a wizard circles its target, 1.0 or -1.0
+    strafe: f32,
+    rng: MatchRng,
+}
+
+impl Bot {
+    //extra distance past the tip of the weapon at which we'll swing or parry
+    const REACH_SLACK: f32 = 0.3;
+    //a wizard tries to stay this far from its target
+    const KITE_DISTANCE: f32 = 5.0;
+    //projectiles further away than this are ignored
+    const DANGER_RANGE: f32 = 4.0;
+    //projectiles passing closer than this to us are dodged
+    const DANGER_WIDTH: f32 = 0.8;
+
+    pub fn new(slot: usize, difficulty: Difficulty, seed: u64) -> Self {
+        Self {
+            slot,
+            difficulty,
+            wait: 0,
+            seen_attack: None,
+            strafe: 1.0,
+            rng: MatchRng::new(seed.wrapping_add(slot as u64)),
+        }
+    }
+
+    ///advances the bot by a tick; when it's time to look again, returns the inputs to send
+    pub fn update(&mut self, me: Handle<Node>, players: &[Handle<Node>], graph: &Graph) -> Vec<PadInput> {
+        let rng = &mut self.rng;
+        if self.wait > 1 {
+            self.wait -= 1;
+            return Vec::new();
+        }
+        //a little jitter so bots don't all move in lockstep
+        let reaction = self.difficulty.reaction();
+        self.wait = reaction + rng.gen_range(0..=reaction / 2);
+
+        let player = match graph.try_get(me).and_then(|n| n.try_get_script::<Player>()) {
+            Some(player) if !player.state.is_dead() => player,
+            _ => return Vec::new(),
+        };
+        let sight = Self::look(me, players, graph);
+        let mut inputs = Vec::new();
+
+        //get out of the way of projectiles first
+        if let Some(dir) = sight.incoming {
+            //whichever side of the projectile's path is further from the enemy
+            let side = Vector2::new(-dir.y, dir.x);
+            let side = match sight.enemy {
+                Some((_, at, _)) if side.dot(&(at - sight.position)) > 0.0 => -side,
+                _ => side,
+            };
+            Self::steer(side, &mut inputs);
+            return inputs;
+        }
+
+        let (enemy, at, state) = match sight.enemy {
+            Some(enemy) => enemy,
+            None => {
+                Self::steer(Vector2::new(0.0, 0.0), &mut inputs);
+                return inputs;
+            },
+        };
+        let to_enemy = at - sight.position;
+        let distance = to_enemy.norm();
+        let behavior = player.class.behavior();
+        let reach = behavior.weapon_offset() + behavior.weapon_shape().half_extents.y * 2.0 + Self::REACH_SLACK;
+
+        Self::aim(to_enemy, &mut inputs);
+
+        //parry a swing we can see coming, deciding once per swing
+        if let PlayerState::Attacking(_) = state {
+            if distance < reach + Self::REACH_SLACK && self.seen_attack != Some(enemy) {
+                self.seen_attack = Some(enemy);
+                if rng.chance(self.difficulty.parry_chance()) {
+                    Self::steer(Vector2::new(0.0, 0.0), &mut inputs);
+                    inputs.push(PadInput::Pressed(RightThumb));
+                    return inputs;
+                }
+            }
+        } else {
+            self.seen_attack = None;
+        }
+
+        match player.class {
+            Class::Wizard => {
+                //back off if they're close, close in if they're far, and circle them in between
+                let toward = to_enemy / distance.max(0.01);
+                let circle = Vector2::new(-toward.y, toward.x) * self.strafe;
+                let steer = if distance < Self::KITE_DISTANCE - 1.0 {
+                    -toward + circle * 0.5
+                } else if distance > Self::KITE_DISTANCE + 1.0 {
+                    toward
+                } else {
+                    circle
+                };
+                Self::steer(steer, &mut inputs);
+                if rng.chance(0.1) {
+                    self.strafe = -self.strafe;
+                }
+                if player.cooldown > Class::RCOOL {
+                    inputs.push(PadInput::Pressed(LeftTrigger));
+                }
+            },
+            Class::Fighter if distance > reach * 2.0 && player.charges > 0 && player.cooldown > Class::RCOOL => {
+                Self::steer(to_enemy, &mut inputs);
+                inputs.push(PadInput::Pressed(LeftTrigger));
+            },
+            _ => {
+                //close in, and swing once in range
+                if distance > reach * 0.8 {
+                    Self::steer(to_enemy, &mut inputs);
+                } else {
+                    Self::steer(Vector2::new(0.0, 0.0), &mut inputs);
+                }
+                if distance < reach && player.state == PlayerState::Idle && rng.chance(self.difficulty.aggression()) {
+                    inputs.push(PadInput::Pressed(RightTrigger));
+                }
+            },
+        }
+        inputs
+    }
+
+    //finds the nearest living enemy and the most urgent incoming projectile
+    fn look(me: Handle<Node>, players: &[Handle<Node>], graph: &Graph) -> Sight {
+        let at = graph[me].global_position();
+        let position = Vector2::new(at.x, at.y);
+
+        let mut enemy: Option<(Handle<Node>, Vector2<f32>, PlayerState)> = None;
+        for handle in players.iter().filter(|h| **h != me) {
+            let node = match graph.try_get(*handle) {
+                Some(node) => node,
+                None => continue,
+            };
+            let player = match node.try_get_script::<Player>() {
+                Some(player) if !player.state.is_dead() => player,
+                _ => continue,
+            };
+            let at = node.global_position();
+            let at = Vector2::new(at.x, at.y);
+            if enemy.as_ref().map_or(true, |(_, best, _)| (at - position).norm() < (best - position).norm()) {
+                enemy = Some((*handle, at, player.state.clone()));
+            }
+        }
+
+        let mut incoming: Option<(f32, Vector2<f32>)> = None;
+        for node in graph.linear_iter() {
+            let projectile = match node.try_get_script::<Projectile>() {
+                Some(projectile) if projectile.owner != me && !projectile.hit => projectile,
+                _ => continue,
+            };
+            let velocity = node.cast::<RigidBody>().map_or(Vector2::new(0.0, 0.0), |b| b.lin_vel());
+            let speed = velocity.norm();
+            if speed <= 0.0 {
+                continue;
+            }
+            let p = node.global_position();
+            let offset = position - Vector2::new(p.x, p.y);
+            let dir = velocity / speed;
+            //how far along its path the projectile passes us, and how close
+            let along = offset.dot(&dir);
+            let across = (offset - dir * along).norm();
+            if along > 0.0 && along < Self::DANGER_RANGE && across < Self::DANGER_WIDTH
+                && incoming.map_or(true, |(best, _)| along < best) {
+                incoming = Some((along, dir));
+            }
+        }
+
+        Sight {position, enemy, incoming: incoming.map(|(_, dir)| dir)}
+    }
+
+    //left stick: move in a direction (zero stops). the stick's x axis is mirrored (see Player::moveplayer)
+    fn steer(dir: Vector2<f32>, inputs: &mut Vec<PadInput>) {
+        let dir = if dir.norm() > 0.0 {dir.normalize()} else {dir};
+        inputs.push(PadInput::Axis(g::Axis::LeftStickX, -dir.x));
+        inputs.push(PadInput::Axis(g::Axis::LeftStickY, dir.y));
+    }
+
+    //right stick: face a direction, mirrored the same way. a zero on the right stick is ignored,
+    // so nudge exact zeros to keep the old facing from leaking through
+    fn aim(dir: Vector2<f32>, inputs: &mut Vec<PadInput>) {
+        if dir.norm() <= 0.0 {
+            return;
+        }
+        let dir = dir.normalize();
+        let nudge = |v: f32| if v == 0.0 {0.001} else {v};
+        inputs.push(PadInput::Axis(g::Axis::RightStickX, nudge(-dir.x)));
+        inputs.push(PadInput::Axis(g::Axis::RightStickY, nudge(dir.y)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    fn axis(inputs: &[PadInput], which: g::Axis) -> Option<f32> {
+        inputs.iter().rev().find_map(|i| match i {
+            PadInput::Axis(a, v) if *a == which => Some(*v),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn harder_bots_react_faster_and_parry_more() {
+        for pair in ALL.windows(2) {
+            let (easier, harder) = (pair[0], pair[1]);
+            assert!(harder.reaction() < easier.reaction(), "{harder:?} vs {easier:?}");
+            assert!(harder.parry_chance() > easier.parry_chance(), "{harder:?} vs {easier:?}");
+            assert!(harder.aggression() > easier.aggression(), "{harder:?} vs {easier:?}");
+        }
+        for difficulty in ALL {
+            assert!(difficulty.reaction() > 0);
+            assert!((0.0..=1.0).contains(&difficulty.parry_chance()));
+            assert!((0.0..=1.0).contains(&difficulty.aggression()));
+        }
+    }
+
+    #[test]
+    fn difficulty_menu_wraps_around() {
+        assert_eq!(Difficulty::Easy.next(), Difficulty::Medium);
+        assert_eq!(Difficulty::Medium.next(), Difficulty::Hard);
+        assert_eq!(Difficulty::Hard.next(), Difficulty::Easy);
+    }
+
+    #[test]
+    fn steering_mirrors_the_left_stick_x_axis() {
+        let mut inputs = Vec::new();
+        Bot::steer(Vector2::new(3.0, 4.0), &mut inputs);
+        //normalised, with x flipped the way Player::moveplayer flips it back
+        assert_eq!(axis(&inputs, g::Axis::LeftStickX), Some(-0.6));
+        assert_eq!(axis(&inputs, g::Axis::LeftStickY), Some(0.8));
+
+        let mut inputs = Vec::new();
+        Bot::steer(Vector2::new(0.0, 0.0), &mut inputs);
+        assert_eq!(axis(&inputs, g::Axis::LeftStickX), Some(0.0));
+        assert_eq!(axis(&inputs, g::Axis::LeftStickY), Some(0.0));
+    }
+
+    #[test]
+    fn aiming_mirrors_the_right_stick_and_never_sends_zero() {
+        let mut inputs = Vec::new();
+        Bot::aim(Vector2::new(-2.0, 0.0), &mut inputs);
+        assert_eq!(axis(&inputs, g::Axis::RightStickX), Some(1.0));
+        //a zero would be ignored by the player and leave the old facing in place
+        assert_eq!(axis(&inputs, g::Axis::RightStickY), Some(0.001));
+
+        let mut inputs = Vec::new();
+        Bot::aim(Vector2::new(0.0, 0.0), &mut inputs);
+        assert!(inputs.is_empty());
+    }
+}
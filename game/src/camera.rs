@@ -0,0 +1,174 @@
+//this module contains the camera rig, which moves and zooms the scene's camera
+// so every living player stays in view:
+// it aims at the middle of the box around the living players (dead ones are ignored)
+// it zooms out until that box plus a margin fits on screen, but never closer than MIN_SIZE
+// it eases towards that instead of jumping, so deaths and respawns don't snap the view
+// it never shows past the edges of the arena, which it works out from the walls (every
+//  collider in the scene when it loads), so any arena scene can be used
+// screen shake from feedback.rs goes on top, after the clamp
+//a spectator (see spectator.rs) can hand it a point to look at instead
+use crate::*;
+use fyrox::{
+    core::algebra::Point3,
+    scene::camera::{Camera, Projection},
+};
+
+pub struct CameraRig {
+    //the lower-left and upper-right corners of the arena, in world units; None if the scene
+    // has no walls, in which case the camera goes wherever the players do
+    pub arena: Option<(Vector2<f32>, Vector2<f32>)>,
+    //where the camera is looking and half the height of the view, once we've picked the camera up
+    center: Option<Vector2<f32>>,
+    size: f32,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        Self {
+            arena: None,
+            center: None,
+            size: Self::MIN_SIZE,
+        }
+    }
+}
+
+impl CameraRig {
+    //world units kept clear around the outermost players
+    const MARGIN: f32 = 2.0;
+    //how far in the camera will zoom, as half the view height
+    const MIN_SIZE: f32 = 3.0;
+    //fraction of the way to the target the camera moves each frame
+    const EASE: f32 = 0.08;
+
+    ///a rig for a freshly loaded scene, kept inside the box around its walls
+    pub fn for_scene(graph: &Graph) -> Self {
+        Self {
+            arena: arena_bounds(graph),
+            ..Self::default()
+        }
+    }
+
+    ///where the camera is looking, without shake
+    pub fn center(&self) -> Option<Vector2<f32>> {
+        self.center
+    }
+
+    ///moves the camera; called once per frame. `focus` is a point a spectator wants to look at
+    pub fn update(&mut self, graph: &mut Graph, screen: Vector2<f32>, players: &[Handle<Node>], focus: Option<Vector2<f32>>, shake: Vector3<f32>) {
+        let (camera, node) = match graph.find(graph.get_root(), &mut |n| n.cast::<Camera>().is_some()) {
+            Some((camera, node)) => (camera, node),
+            None => return,
+        };
+        let z = node.local_transform().position().z;
+        let aspect = if screen.y > 0.0 {screen.x / screen.y} else {1.0};
+
+        //start from wherever the scene put the camera
+        let center = match self.center {
+            Some(center) => center,
+            None => {
+                let position = node.local_transform().position();
+                if let Some(Projection::Orthographic(ortho)) = node.cast::<Camera>().map(|c| c.projection()) {
+                    self.size = ortho.vertical_size;
+                }
+                Vector2::new(position.x, position.y)
+            },
+        };
+
+        //the box around everyone still alive
+        let mut low: Option<Vector2<f32>> = None;
+        let mut high: Option<Vector2<f32>> = None;
+        for handle in players {
+            let player = match graph.try_get(*handle) {
+                Some(player) => player,
+                None => continue,
+            };
+            if player.try_get_script::<Player>().map_or(true, |p| p.state.is_dead()) {
+                continue;
+            }
+            let at = player.global_position();
+            let at = Vector2::new(at.x, at.y);
+            low = Some(low.map_or(at, |l| l.inf(&at)));
+            high = Some(high.map_or(at, |h| h.sup(&at)));
+        }
+
+        let (target, target_size) = match (focus, low, high) {
+            (Some(focus), ..) => (focus, self.size),
+            (None, Some(low), Some(high)) => {
+                let half = (high - low) / 2.0;
+                let size = (half.y + Self::MARGIN).max((half.x + Self::MARGIN) / aspect).max(Self::MIN_SIZE);
+                ((low + high) / 2.0, size)
+            },
+            //nobody to look at; stay put
+            _ => (center, self.size),
+        };
+
+        //never zoom out past the arena
+        let target_size = match self.arena {
+            Some((min, max)) => {
+                let arena = (max - min) / 2.0;
+                target_size.min(arena.y).min(arena.x / aspect)
+            },
+            None => target_size,
+        };
+
+        self.size += (target_size - self.size) * Self::EASE;
+        let center = self.clamp(center + (target - center) * Self::EASE, aspect);
+        self.center = Some(center);
+
+        graph[camera].local_transform_mut().set_position(Vector3::new(center.x, center.y, z) + shake);
+        if let Some(camera) = graph[camera].cast_mut::<Camera>() {
+            if let Projection::Orthographic(mut ortho) = camera.projection().clone() {
+                ortho.vertical_size = self.size;
+                camera.set_projection(Projection::Orthographic(ortho));
+            }
+        }
+    }
+
+    //keeps the view inside the arena; if the view is wider than the arena, centers it instead
+    fn clamp(&self, center: Vector2<f32>, aspect: f32) -> Vector2<f32> {
+        let (min, max) = match self.arena {
+            Some(arena) => arena,
+            None => return center,
+        };
+        let half = Vector2::new(self.size * aspect, self.size);
+        let axis = |c: f32, min: f32, max: f32, half: f32| {
+            if max - min <= half * 2.0 {
+                (min + max) / 2.0
+            } else {
+                c.clamp(min + half, max - half)
+            }
+        };
+        Vector2::new(
+            axis(center.x, min.x, max.x, half.x),
+            axis(center.y, min.y, max.y, half.y),
+        )
+    }
+}
+
+///the box around every 2d collider in the graph. meant for a scene that's just been loaded,
+/// before any players or projectiles are in it, so the only colliders are the walls
+pub fn arena_bounds(graph: &Graph) -> Option<(Vector2<f32>, Vector2<f32>)> {
+    let mut bounds: Option<(Vector2<f32>, Vector2<f32>)> = None;
+    for node in graph.linear_iter() {
+        if !node.is_collider2d() {
+            continue;
+        }
+        //boxes count all four corners; anything else just counts its center
+        let corners = match node.as_collider2d().shape() {
+            ColliderShape::Cuboid(cuboid) => {
+                let h = cuboid.half_extents;
+                vec![(-h.x, -h.y), (h.x, -h.y), (h.x, h.y), (-h.x, h.y)]
+            },
+            _ => vec![(0.0, 0.0)],
+        };
+        for (x, y) in corners {
+            let at = node.global_transform().transform_point(&Point3::new(x, y, 0.0));
+            let at = Vector2::new(at.x, at.y);
+            bounds = Some(match bounds {
+                Some((low, high)) => (low.inf(&at), high.sup(&at)),
+                None => (at, at),
+            });
+        }
+    }
+    bounds
+}
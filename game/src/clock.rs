@@ -0,0 +1,49 @@
+//this module contains the Clock, the fixed-step clock the gameplay simulation runs on.
+//every gameplay timer (cooldowns, iframes, hitstun, respawns, attack frame data, projectile life)
+// counts ticks of this clock instead of on_update calls. the executor already runs its updates
+// on a fixed step of 1/TICK_RATE seconds (running several updates after a slow frame), so one
+// engine update is at most one tick: Game::update decides whether this update's tick runs, and
+// scripts run their per-tick logic if it does (see sim_tick). hit-stop or a paused replay skip
+// the tick. never more than one, since physics steps once per update; anything that needs to go
+// faster (rollback catching up, a fast-forwarding replay) asks the executor for more updates.
+use crate::*;
+
+//ticks per second; the executors run their updates at this rate. telemetry counts in ticks too,
+// so it's defined alongside the telemetry format
+pub use telemetry_schema::TICK_RATE;
+
+#[derive(Debug, Default)]
+pub struct Clock {
+    //ticks simulated since the match started
+    pub tick: u32,
+    //whether scripts run a tick this update
+    pub ticking: bool,
+}
+
+impl Clock {
+    //length of a tick in seconds
+    pub const STEP: f32 = 1.0 / TICK_RATE as f32;
+
+    ///converts ticks to seconds
+    pub fn seconds(ticks: i32) -> f32 {
+        ticks as f32 * Self::STEP
+    }
+
+    ///records whether this update's tick runs
+    pub fn run(&mut self, ticking: bool) {
+        self.ticking = ticking;
+        if ticking {
+            self.tick += 1;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+///whether scripts should run a simulation tick this update; not while the simulation is frozen
+/// (hit-stop, a paused replay). without a Game plugin (e.g. in the editor) scripts tick every update
+pub fn sim_tick(plugins: &[Box<dyn Plugin>]) -> bool {
+    game(plugins).map_or(true, |game| game.clock.ticking)
+}
@@ -22,6 +22,12 @@ pub fn create_text_with_background(ui: &mut UserInterface, text: &str, x: f32, y
     return text_widget;
 }
 
+///shows or hides a widget made by create_text_with_background, along with its background
+pub fn set_text_visibility(ui: &mut UserInterface, text: Handle<UiNode>, visible: bool) {
+    let border = ui.node(text).parent();
+    ui.send_message(WidgetMessage::visibility(border, MessageDirection::ToWidget, visible));
+}
+
 pub fn create_cube_rigid_body(graph: &mut Graph) -> Handle<Node> {
     RigidBodyBuilder::new(BaseBuilder::new().with_children(&[
             // Rigid body must have at least one collider
@@ -49,7 +55,7 @@ pub fn create_kinematic_rigid_body(graph: &mut Graph) -> Handle<Node> {
     .build(graph)
 }
 
-pub fn create_rect(graph: &mut Graph, resource_manager: &ResourceManager, color: &Vec<u8>, shape: String) -> Handle<Node> {
+pub fn create_rect(graph: &mut Graph, resource_manager: &ResourceManager, color: Color, shape: String) -> Handle<Node> {
     RectangleBuilder::new(
         BaseBuilder::new().with_local_transform(
             TransformBuilder::new()
@@ -59,7 +65,7 @@ pub fn create_rect(graph: &mut Graph, resource_manager: &ResourceManager, color:
         ),
     )
     .with_texture(resource_manager.request::<Texture, _>(shape))
-    .with_color(Color{r: color[0], g: color[1], b: color[2], a: 255})
+    .with_color(color)
     .build(graph)
 }
 
@@ -83,13 +89,7 @@ pub fn create_centered_text(ui: &mut UserInterface, text: &str) -> Handle<UiNode
 
 pub fn create_weapon_body(class: &Class, context: &mut PluginContext, game: &Game) -> Handle<Node> {
     //setting up melee weapon
-    let weapontype = match class {
-        Class::Barbarian => Class::BARBWEP,
-        Class::Rogue => Class::ROGWEP,
-        Class::Wizard => Class::WIZWEP,
-        Class::Fighter => Class::FIGWEP,
-
-    };
+    let weapontype = class.behavior().weapon_shape();
     RigidBodyBuilder::new(BaseBuilder::new().with_children(&[
         RectangleBuilder::new(
             BaseBuilder::new().with_local_transform(
@@ -117,43 +117,27 @@ pub fn create_weapon_body(class: &Class, context: &mut PluginContext, game: &Gam
 }
 
 //create and position a new player object
-pub fn create_player(player_num: i8, player_class: Class, id: GamepadId, context: &mut PluginContext, game: &mut Game) {
-    let mut player_data = (Vec::<u8>::new(), Vec::<f32>::new());
-
-    if player_num == 1 {
-        player_data.0 = Vec::from([66, 245, 158]);
-        player_data.1 = Vec::from([6.0, 3.0, 0.0]);
-    }
-    else if player_num == 2 {
-        player_data.0 = Vec::from([66, 167, 245]);
-        player_data.1 = Vec::from([-6.0, 3.0, 0.0]);
-    }
-    else if player_num == 3 {
-        player_data.0 = Vec::from([194, 136, 252]);
-        player_data.1 = Vec::from([-6.0, -3.0, 0.0]);
-    }
-    else if player_num == 4 {
-        player_data.0 = Vec::from([250, 135, 215]);
-        player_data.1 = Vec::from([6.0, -3.0, 0.0]);
-    }
-    else {
-        println!("Player cap reached");
-        return;
-    }
+///creates the player in the given slot (player number - 1)
+pub fn create_player(slot: usize, player_class: Class, context: &mut PluginContext, game: &mut Game) {
+    let spawn = match SPAWN_POINTS.get(slot) {
+        Some(spawn) => *spawn,
+        None => {
+            warn!("Player cap reached");
+            return;
+        },
+    };
+    //team colour from the player's profile, or the palette in the options
+    let color = game.profiles.get(slot).and_then(|p| p.color()).unwrap_or(game.options.palette.color(slot));
 
     //path to correct sprite, pre-coloring based on team
-    let path = match player_class.clone() {
-        Class::Barbarian => {"data/White_square.png".to_string()},
-        Class::Fighter => {"data/White_circle.png".to_string()},
-        Class::Rogue => {"data/White_triangle.png".to_string()},
-        Class::Wizard => {"data/White_star.png".to_string()},
-
-    };
+    let path = player_class.behavior().sprite().to_string();
 
     //create a new player
     let player_handle = create_cube_rigid_body(&mut context.scenes[game.scene].graph);
     //create a sprite for the player
-    let sprite_handle = create_rect(&mut context.scenes[game.scene].graph, context.resource_manager, &player_data.0, path);
+    let sprite_handle = create_rect(&mut context.scenes[game.scene].graph, context.resource_manager, color, path.clone());
+    //emblem and outline, if they're turned on
+    add_markers(&mut context.scenes[game.scene].graph, context.resource_manager, sprite_handle, slot, &path, &game.options);
     //make the sprite a child of the player
     context.scenes[game.scene].graph.link_nodes(sprite_handle, player_handle);
     //make a weapon rigid body / collider
@@ -165,71 +149,32 @@ pub fn create_player(player_num: i8, player_class: Class, id: GamepadId, context
         Weapon{
             player: player_handle.clone(),
             class: player_class.clone(),
+            ..Default::default()
         }
     );
     //add the player to the game's struct
-    game.players.insert(id, player_handle);
-    // add player ID to vector of IDs
-    game.id_list.push(id);
+    game.players.insert(slot, player_handle);
 
-    match player_class {
-        Class::Barbarian => {
-            set_script(&mut context.scenes[game.scene].graph[player_handle.clone()], 
-            Player{
-                class: Class::Barbarian,
-                state: PlayerState::Idle,
-                weapon: weapon_handle,
-                    cooldown: 0,
-                    facing: Vector3::new(0.0,1.0,0.0),
-                health: Class::BARBHEALTH,
-                charges: 0,
-                iframes: 0,
-            })
-        },
-        Class::Fighter => {
-            set_script(&mut context.scenes[game.scene].graph[player_handle.clone()], 
-            Player{
-                class: Class::Fighter,
-                state: PlayerState::Idle,
-                weapon: weapon_handle,
-                    cooldown: 0,
-                    facing: Vector3::new(0.0,1.0,0.0),
-                health: Class::FIGHEALTH,
-                charges: 0,
-                iframes: 0,
-            })
-        },
-        Class::Rogue => {
-            set_script(&mut context.scenes[game.scene].graph[player_handle.clone()], 
-            Player{
-            class: Class::Rogue,
-            state: PlayerState::Idle,
-            weapon: weapon_handle,
-                cooldown: 0,
-                facing: Vector3::new(0.0,1.0,0.0),
-            health: Class::ROGHEALTH,
-            charges: 0,
-            iframes: 0,
-            })
-        },
-        Class::Wizard => {
-            set_script(&mut context.scenes[game.scene].graph[player_handle.clone()], 
-            Player{
-            class: Class::Wizard,
+    let health = player_class.behavior().health();
+    set_script(&mut context.scenes[game.scene].graph[player_handle.clone()], 
+        Player{
+            class: player_class,
             state: PlayerState::Idle,
             weapon: weapon_handle,
-                cooldown: 0,
-                facing: Vector3::new(0.0,1.0,0.0),
-            health: Class::WIZHEALTH,
+            cooldown: 0,
+            facing: Vector3::new(0.0,1.0,0.0),
+            health,
             charges: 0,
             iframes: 0,
-        })
+            color,
+            stocks: game.rules.stocks,
+            ..Default::default()
         }
-    }
+    );
 
     context.scenes[game.scene].graph[player_handle.clone()]
         .local_transform_mut()
-        .set_position(Vector3::new(player_data.1[0], player_data.1[1], player_data.1[2]));
+        .set_position(Vector3::new(spawn[0], spawn[1], spawn[2]));
 }
 
 ///create a new button with position x,y, dimensions w,h, text, and optional alignment.
@@ -260,7 +205,8 @@ pub fn create_player_class_button(
 }
 
 
-pub fn create_facing_chevron(facing: Vector3<f32>, context: &mut ScriptContext) -> Handle<Node> {
+///the arrow showing which way a player faces, in their team colour
+pub fn create_facing_chevron(facing: Vector3<f32>, color: Color, context: &mut ScriptContext) -> Handle<Node> {
     //let mut trans = context.scene.graph[context.handle.clone()].local_transform().clone();
     let mut trans = Transform::identity();
     let mut off = facing.clone();
@@ -276,6 +222,7 @@ pub fn create_facing_chevron(facing: Vector3<f32>, context: &mut ScriptContext)
             )
         )
         .with_texture(context.resource_manager.request::<Texture, _>("data/White_chevron.png"))
+        .with_color(color)
         .build(&mut context.scene.graph),
         // ColliderBuilder::new(BaseBuilder::new())
         //         .with_shape(fyrox::scene::dim2::collider::ColliderShape::Triangle(TriangleShape{
@@ -294,6 +241,26 @@ pub fn create_facing_chevron(facing: Vector3<f32>, context: &mut ScriptContext)
     return chevron;
 }
 
+///a projectile with no script or collider, for clients to show the projectiles the host simulates
+pub fn create_projectile_standin(graph: &mut Graph, resource_manager: &ResourceManager) -> Handle<Node> {
+    let proj = RigidBodyBuilder::new(BaseBuilder::new().with_children(&[
+        RectangleBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_scale(Vector3::new(0.3, 0.5, 1.0))
+                    .build()
+            )
+        )
+            .with_texture(resource_manager.request::<Texture, _>("data/white_rectangle.png"))
+            .build(graph),
+        ]))
+        .with_gravity_scale(0.0)
+        .with_can_sleep(false)
+        .build(graph);
+    attach_trail(graph, proj);
+    proj
+}
+
 pub fn create_projectile(facing: Vector3<f32>, ctx: &mut ScriptMessageContext) -> Handle<Node> {
     let mut trans = ctx.scene.graph[ctx.handle].local_transform().clone();
         let mut dirvec = facing;
@@ -303,6 +270,13 @@ pub fn create_projectile(facing: Vector3<f32>, ctx: &mut ScriptMessageContext) -
         let mut spd = Vector2::new(facing[0],facing[1]);
         spd.set_magnitude(Class::RATKSPD);
 
+    spawn_projectile(&mut ctx.scene.graph, ctx.resource_manager, trans, spd,
+        Projectile{owner: ctx.handle, facing: facing.clone(), hit: false, life: Class::PROJLIFE})
+}
+
+///builds a projectile body with the given transform, velocity and script.
+/// create_projectile fires a new one; rollback uses this to put old ones back
+pub fn spawn_projectile(graph: &mut Graph, resource_manager: &ResourceManager, trans: Transform, spd: Vector2<f32>, script: Projectile) -> Handle<Node> {
         let proj = RigidBodyBuilder::new(BaseBuilder::new().with_children(&[
             RectangleBuilder::new(
                 BaseBuilder::new().with_local_transform(
@@ -312,13 +286,13 @@ pub fn create_projectile(facing: Vector3<f32>, ctx: &mut ScriptMessageContext) -
                         .build()
                 )
             )
-                .with_texture(ctx.resource_manager.request::<Texture, _>("data/white_rectangle.png"))
-                .build(&mut ctx.scene.graph),
+                .with_texture(resource_manager.request::<Texture, _>("data/white_rectangle.png"))
+                .build(graph),
             // Rigid body must have at least one collider
             ColliderBuilder::new(BaseBuilder::new())
                 .with_shape(ColliderShape::cuboid(0.15, 0.25))
                 .with_sensor(true)
-                .build(&mut ctx.scene.graph),
+                .build(graph),
             
             ])
             .with_local_transform(trans)
@@ -327,10 +301,9 @@ pub fn create_projectile(facing: Vector3<f32>, ctx: &mut ScriptMessageContext) -
         .with_lin_vel(spd)
         .with_can_sleep(false)
         .with_ccd_enabled(true)
-        .build(&mut ctx.scene.graph);
-    set_script(&mut ctx.scene.graph[proj.clone()], 
-                Projectile{facing: facing.clone(), hit: false, life: 120}
-                     );
+        .build(graph);
+    attach_trail(graph, proj);
+    set_script(&mut graph[proj.clone()], script);
 
     return proj;
 }
\ No newline at end of file
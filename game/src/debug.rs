@@ -0,0 +1,180 @@
+//this module contains the F2 debug overlay, drawn with the scene's debug lines:
+// every weapon collider: red while it's a sensor (swinging), blue while it's solid (parrying)
+// projectile colliders in orange, player bodies in green, and anything else (walls) in grey
+// every active contact point in the 2d physics world as a yellow cross, with a tick along the
+//  contact normal
+// each player's facing vector in white
+// a label over each player with their PlayerState and frame counter (and attack phase), iframes and cooldown
+//the spectator's hitbox toggle (see spectator.rs) draws the same colliders and contacts, without the labels
+use crate::*;
+use fyrox::{
+    core::algebra::Point3,
+    scene::{camera::Camera, debug::Line, dim2::collider::Collider},
+};
+
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    //a label for each player, made the first time we see them
+    labels: HashMap<Handle<Node>, Handle<UiNode>>,
+}
+
+impl DebugOverlay {
+    const SENSOR: Color = Color::opaque(255, 60, 60);
+    const SOLID: Color = Color::opaque(60, 120, 255);
+    const PROJECTILE: Color = Color::opaque(255, 160, 0);
+    const BODY: Color = Color::opaque(60, 220, 60);
+    const OTHER: Color = Color::opaque(128, 128, 128);
+    const FACING: Color = Color::opaque(255, 255, 255);
+    const CONTACT: Color = Color::opaque(255, 240, 0);
+    //half the size of a contact cross, and the length of its normal tick
+    const CONTACT_SIZE: f32 = 0.1;
+    const NORMAL_LENGTH: f32 = 0.3;
+    //segments in a drawn circle
+    const CIRCLE_SEGMENTS: usize = 16;
+
+    pub fn toggle(&mut self, ui: &mut UserInterface) {
+        self.visible = !self.visible;
+        for label in self.labels.values() {
+            ui.send_message(WidgetMessage::visibility(*label, MessageDirection::ToWidget, self.visible));
+        }
+    }
+
+    ///redraws everything; called once per frame. `hitboxes` draws the colliders even while hidden
+    pub fn update(&mut self, scene: &mut Scene, ui: &mut UserInterface, players: &[Handle<Node>], hitboxes: bool) {
+        scene.drawing_context.clear_lines();
+        if self.visible || hitboxes {
+            self.draw_colliders(scene);
+            self.draw_contacts(scene);
+        }
+        if !self.visible {
+            return;
+        }
+
+        let graph = &scene.graph;
+        let camera = graph.find(graph.get_root(), &mut |n| n.cast::<Camera>().is_some())
+            .and_then(|(_, n)| n.cast::<Camera>());
+        let screen = ui.screen_size();
+
+        //forget the labels of players who are gone
+        self.labels.retain(|player, label| {
+            let keep = players.contains(player);
+            if !keep {
+                ui.send_message(WidgetMessage::remove(*label, MessageDirection::ToWidget));
+            }
+            keep
+        });
+
+        for handle in players {
+            let node = match graph.try_get(*handle) {
+                Some(node) => node,
+                None => continue,
+            };
+            let player = match node.try_get_script::<Player>() {
+                Some(player) => player,
+                None => continue,
+            };
+            let at = node.global_position();
+
+            let mut facing = player.facing;
+            if facing.norm() > 0.0 {
+                facing.set_magnitude(1.0);
+            }
+            scene.drawing_context.add_line(Line{begin: at, end: at + facing, color: Self::FACING});
+
+            let label = *self.labels.entry(*handle).or_insert_with(|| {
+                TextBuilder::new(WidgetBuilder::new().with_foreground(Brush::Solid(Color::opaque(255, 255, 255))))
+                    .build(&mut ui.build_ctx())
+            });
+            let mut text = format!("{:?}", player.state);
+            if let PlayerState::Attacking(frame) = player.state {
+                text += &format!(" {:?}", player.class.behavior().melee().frames.phase(frame));
+            }
+            text += &format!("\niframes {} cooldown {}", player.iframes, player.cooldown);
+            ui.send_message(TextMessage::text(label, MessageDirection::ToWidget, text));
+            if let Some(pos) = camera.and_then(|c| c.project(at + Vector3::new(0.0, 1.0, 0.0), screen)) {
+                ui.send_message(WidgetMessage::desired_position(label, MessageDirection::ToWidget, pos));
+            }
+        }
+    }
+
+    fn draw_colliders(&self, scene: &mut Scene) {
+        let graph = &scene.graph;
+        for node in graph.linear_iter() {
+            if !node.is_collider2d() || !node.global_visibility() {
+                continue;
+            }
+            let collider = node.as_collider2d();
+            let parent = graph.try_get(node.parent());
+            let color = if parent.map_or(false, |p| p.try_get_script::<Weapon>().is_some()) {
+                if collider.is_sensor() {Self::SENSOR} else {Self::SOLID}
+            } else if parent.map_or(false, |p| p.try_get_script::<Projectile>().is_some()) {
+                Self::PROJECTILE
+            } else if parent.map_or(false, |p| p.try_get_script::<Player>().is_some()) {
+                Self::BODY
+            } else {
+                Self::OTHER
+            };
+            for line in Self::outline(node, collider, color) {
+                scene.drawing_context.add_line(line);
+            }
+        }
+    }
+
+    fn draw_contacts(&self, scene: &mut Scene) {
+        let graph = &scene.graph;
+        let mut lines = Vec::new();
+        for (handle, node) in graph.pair_iter() {
+            if !node.is_collider2d() {
+                continue;
+            }
+            for pair in node.as_collider2d().contacts(&graph.physics2d) {
+                //each pair turns up once for each of its colliders; only draw it once
+                if pair.collider1 != handle || !pair.has_any_active_contact {
+                    continue;
+                }
+                //contact points are stored relative to the first collider
+                let transform = node.global_transform();
+                for manifold in &pair.manifolds {
+                    let normal = Vector3::new(manifold.normal.x, manifold.normal.y, 0.0) * Self::NORMAL_LENGTH;
+                    for contact in &manifold.points {
+                        let at = transform.transform_point(&Point3::new(contact.local_p1.x, contact.local_p1.y, 0.0)).coords;
+                        let (dx, dy) = (Vector3::new(Self::CONTACT_SIZE, 0.0, 0.0), Vector3::new(0.0, Self::CONTACT_SIZE, 0.0));
+                        lines.push(Line{begin: at - dx, end: at + dx, color: Self::CONTACT});
+                        lines.push(Line{begin: at - dy, end: at + dy, color: Self::CONTACT});
+                        lines.push(Line{begin: at, end: at + normal, color: Self::CONTACT});
+                    }
+                }
+            }
+        }
+        for line in lines {
+            scene.drawing_context.add_line(line);
+        }
+    }
+
+    //the outline of a collider's shape, in world space
+    fn outline(node: &Node, collider: &Collider, color: Color) -> Vec<Line> {
+        let transform = node.global_transform();
+        let point = |x: f32, y: f32| transform.transform_point(&Point3::new(x, y, 0.0)).coords;
+        let points: Vec<Vector3<f32>> = match collider.shape() {
+            ColliderShape::Cuboid(cuboid) => {
+                let (w, h) = (cuboid.half_extents.x, cuboid.half_extents.y);
+                vec![point(-w, -h), point(w, -h), point(w, h), point(-w, h)]
+            },
+            ColliderShape::Ball(ball) => (0..Self::CIRCLE_SEGMENTS).map(|i| {
+                let angle = i as f32 / Self::CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                point(angle.cos() * ball.radius, angle.sin() * ball.radius)
+            }).collect(),
+            //anything fancier just gets a cross where it is
+            _ => {
+                let at = point(0.0, 0.0);
+                let (dx, dy) = (Vector3::new(0.2, 0.0, 0.0), Vector3::new(0.0, 0.2, 0.0));
+                return vec![
+                    Line{begin: at - dx, end: at + dx, color},
+                    Line{begin: at - dy, end: at + dy, color},
+                ];
+            },
+        };
+        (0..points.len()).map(|i| Line{begin: points[i], end: points[(i + 1) % points.len()], color}).collect()
+    }
+}
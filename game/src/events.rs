@@ -0,0 +1,65 @@
+//this module contains the game event bus. scripts publish GameEvents with publish() instead of
+// reaching into the Game plugin themselves; Game::update drains the queue once per frame and hands
+// every event to the systems that listen for it (feedback, audio, stats and the hud).
+use crate::*;
+
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    //attacker is the player behind the weapon or projectile, or NONE if there wasn't one
+    PlayerDamaged{ player: Handle<Node>, attacker: Handle<Node>, damage: u32, position: Vector3<f32> },
+    PlayerDied{ player: Handle<Node>, killer: Handle<Node>, position: Vector3<f32> },
+    PlayerRespawned{ player: Handle<Node>, position: Vector3<f32> },
+    //a player started a parry, whether or not anything runs into it
+    ParryStarted{ player: Handle<Node> },
+    //player is the one who parried, attacker the one who got parried
+    ParrySucceeded{ player: Handle<Node>, attacker: Handle<Node>, position: Vector3<f32> },
+    ProjectileFired{ player: Handle<Node>, projectile: Handle<Node>, position: Vector3<f32> },
+    //something shown on the hud changed that none of the above cover (spawning in, charges)
+    PlayerChanged{ player: Handle<Node> },
+}
+
+impl GameEvent {
+    ///every player this event is about
+    pub fn players(&self) -> Vec<Handle<Node>> {
+        match self {
+            GameEvent::PlayerDamaged{player, attacker, ..} => vec![*player, *attacker],
+            GameEvent::PlayerDied{player, killer, ..} => vec![*player, *killer],
+            GameEvent::PlayerRespawned{player, ..} => vec![*player],
+            GameEvent::ParryStarted{player} => vec![*player],
+            GameEvent::ParrySucceeded{player, attacker, ..} => vec![*player, *attacker],
+            GameEvent::ProjectileFired{player, ..} => vec![*player],
+            GameEvent::PlayerChanged{player} => vec![*player],
+        }
+    }
+}
+
+///finds the Game plugin, wherever it is in the plugin list
+pub fn game_mut(plugins: &mut [Box<dyn Plugin>]) -> Option<&mut Game> {
+    plugins.iter_mut().find_map(|p| p.cast_mut::<Game>())
+}
+
+pub fn game(plugins: &[Box<dyn Plugin>]) -> Option<&Game> {
+    plugins.iter().find_map(|p| p.cast::<Game>())
+}
+
+///queues an event for the Game plugin to hand out on its next update
+pub fn publish(plugins: &mut [Box<dyn Plugin>], event: GameEvent) {
+    if let Some(game) = game_mut(plugins) {
+        game.events.push(event);
+    }
+}
+
+///the player behind a weapon or projectile
+pub fn attacker_of(graph: &Graph, source: Handle<Node>) -> Handle<Node> {
+    let node = match graph.try_get(source) {
+        Some(node) => node,
+        None => return Handle::NONE,
+    };
+    if let Some(weapon) = node.try_get_script::<Weapon>() {
+        weapon.player
+    } else if let Some(projectile) = node.try_get_script::<Projectile>() {
+        projectile.owner
+    } else {
+        Handle::NONE
+    }
+}
@@ -0,0 +1,179 @@
+//this module contains the hit feedback effects that make a hit feel like a hit:
+// global hit-stop, where the whole scene freezes for as long as the attacker's frame data says
+// camera shake on heavy hits (worked out here, applied by the camera rig in camera.rs)
+// a white flash on the sprite of the player who got hit
+// damage numbers that pop up over the player and float away
+//Game hands every PlayerDamaged event to Feedback::hit, and Game::update plays them out.
+use crate::*;
+use fyrox::scene::{camera::Camera, dim2::rectangle::Rectangle};
+use rand::Rng;
+
+///how strongly a class's hits are felt; see ClassBehavior::feedback
+#[derive(Debug, Clone, Copy)]
+pub struct FeedbackTuning {
+    //hits doing at least this much damage shake the camera
+    pub shake_threshold: u32,
+    //how far the camera can be thrown, in world units
+    pub shake_strength: f32,
+    //frames the victim flashes white for
+    pub flash_frames: i32,
+}
+
+impl Default for FeedbackTuning {
+    fn default() -> Self {
+        Self {
+            shake_threshold: 4,
+            shake_strength: 0.15,
+            flash_frames: 4,
+        }
+    }
+}
+
+///a floating damage number
+pub struct DamagePopup {
+    pub text: Handle<UiNode>,
+    pub position: Vector3<f32>,
+    pub life: i32,
+}
+
+impl DamagePopup {
+    //frames a damage number stays on screen
+    pub const LIFE: i32 = 40;
+}
+
+//a hit that still needs its popup made; that needs the ui, which scripts can't reach
+struct PendingPopup {
+    position: Vector3<f32>,
+    damage: u32,
+}
+
+#[derive(Default)]
+pub struct Feedback {
+    //ticks of global hit-stop left
+    pub hitstop: i32,
+    //0.0 to 1.0; the camera shakes by the square of this and it decays every frame
+    pub trauma: f32,
+    pub shake_strength: f32,
+    //velocities of every rigid body from when hit-stop froze them
+    frozen: Option<Vec<(Handle<Node>, Vector2<f32>)>>,
+    //how far the camera is thrown off this frame
+    pub shake: Vector3<f32>,
+    popups: Vec<DamagePopup>,
+    pending: Vec<PendingPopup>,
+}
+
+impl Feedback {
+    //how much trauma drains per frame
+    const TRAUMA_DECAY: f32 = 0.05;
+
+    ///called for every PlayerDamaged event
+    pub fn hit(&mut self, position: Vector3<f32>, damage: u32, tuning: FeedbackTuning) {
+        if damage >= tuning.shake_threshold {
+            self.trauma = (self.trauma + 0.5).min(1.0);
+            self.shake_strength = self.shake_strength.max(tuning.shake_strength);
+        }
+
+        self.pending.push(PendingPopup{position, damage});
+    }
+
+    ///the hit-stop part of a hit, which changes how the match plays out; the rest is cosmetic
+    pub fn hit_stop(&mut self, ticks: i32) {
+        self.hitstop = self.hitstop.max(ticks);
+    }
+
+    ///plays out shake and popups; called once per frame from Game::update.
+    /// hit-stop is counted down by Game in simulation ticks; while the simulation is frozen
+    /// (hit-stop, a paused replay) we hold every rigid body still so nothing drifts
+    pub fn update(&mut self, scene: &mut Scene, ui: &mut UserInterface, frozen: bool) {
+        if frozen {
+            if self.frozen.is_none() {
+                self.frozen = Some(freeze_bodies(&mut scene.graph));
+            }
+        } else if let Some(velocities) = self.frozen.take() {
+            for (handle, vel) in velocities {
+                if let Some(body) = scene.graph.try_get_mut(handle).and_then(|n| n.cast_mut::<RigidBody>()) {
+                    body.set_lin_vel(vel);
+                }
+            }
+        }
+
+        self.update_shake();
+
+        for pending in self.pending.drain(..) {
+            let text = TextBuilder::new(WidgetBuilder::new()
+                .with_foreground(Brush::Solid(Color::opaque(255, 255, 255))))
+                .with_text(format!("-{}", pending.damage))
+                .build(&mut ui.build_ctx());
+            self.popups.push(DamagePopup{text, position: pending.position, life: DamagePopup::LIFE});
+        }
+
+        let camera = scene.graph.find(scene.graph.get_root(), &mut |n| n.cast::<Camera>().is_some())
+            .map(|(h, _)| h);
+        let screen = ui.screen_size();
+        for popup in self.popups.iter_mut() {
+            popup.life -= 1;
+            //drift upwards
+            popup.position.y += 0.02;
+            if let Some(camera) = camera.and_then(|h| scene.graph[h].cast::<Camera>()) {
+                if let Some(pos) = camera.project(popup.position, screen) {
+                    ui.send_message(WidgetMessage::desired_position(
+                        popup.text, MessageDirection::ToWidget, pos));
+                }
+            }
+            if popup.life <= 0 {
+                ui.send_message(WidgetMessage::remove(popup.text, MessageDirection::ToWidget));
+            }
+        }
+        self.popups.retain(|p| p.life > 0);
+    }
+
+    fn update_shake(&mut self) {
+        if self.trauma <= 0.0 {
+            self.shake = Vector3::new(0.0, 0.0, 0.0);
+            self.shake_strength = 0.0;
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+        let amount = self.shake_strength * self.trauma * self.trauma;
+        self.shake = Vector3::new(rng.gen_range(-1.0..1.0) * amount, rng.gen_range(-1.0..1.0) * amount, 0.0);
+
+        self.trauma = (self.trauma - Self::TRAUMA_DECAY).max(0.0);
+    }
+}
+
+//stops every rigid body, returning the velocities they had
+fn freeze_bodies(graph: &mut Graph) -> Vec<(Handle<Node>, Vector2<f32>)> {
+    let mut velocities = Vec::new();
+    for (handle, node) in graph.pair_iter_mut() {
+        if let Some(body) = node.cast_mut::<RigidBody>() {
+            velocities.push((handle, body.lin_vel()));
+            body.set_lin_vel(Vector2::new(0.0, 0.0));
+        }
+    }
+    velocities
+}
+
+///how hard a hit from this attacker is felt; hits without an attacker use the default tuning
+pub fn feedback_tuning(graph: &Graph, attacker: Handle<Node>) -> FeedbackTuning {
+    graph.try_get(attacker)
+        .and_then(|n| n.try_get_script::<Player>())
+        .map_or(FeedbackTuning::default(), |p| p.class.behavior().feedback())
+}
+
+///ticks of hit-stop for a hit: the hitstop in the attacker's frame data, or none without an attacker
+pub fn hit_stop_length(graph: &Graph, attacker: Handle<Node>) -> i32 {
+    graph.try_get(attacker)
+        .and_then(|n| n.try_get_script::<Player>())
+        .map_or(0, |p| p.class.behavior().melee().frames.hitstop)
+}
+
+///tints the sprite(s) directly under a player
+pub fn set_sprite_color(graph: &mut Graph, player: Handle<Node>, color: Color) {
+    let children = graph[player].children().to_vec();
+    for child in children {
+        if let Some(sprite) = graph[child].cast_mut::<Rectangle>() {
+            sprite.set_color(color);
+        }
+    }
+}
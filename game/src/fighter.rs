@@ -0,0 +1,46 @@
+//the Fighter: all-rounder that earns a projectile charge for every melee hit it lands
+use crate::*;
+use crate::behavior::{ClassBehavior, MeleeStats};
+use fyrox::scene::dim2::collider::CuboidShape;
+
+pub struct FighterBehavior;
+
+impl ClassBehavior for FighterBehavior {
+    fn health(&self) -> u32 { Class::FIGHEALTH }
+    fn speed(&self) -> f32 { Class::FIGSPD }
+    fn sprite(&self) -> &'static str { "data/White_circle.png" }
+    fn weapon_shape(&self) -> CuboidShape { Class::FIGWEP }
+    fn weapon_offset(&self) -> f32 { 1.0 }
+    fn special_cooldown(&self) -> Option<i32> { Some(Class::RCOOL) }
+
+    fn melee(&self) -> MeleeStats {
+        MeleeStats {
+            frames: Class::FIGFRAMES,
+            swing_speed: Class::FIGWEPSPD,
+            damage: Class::FIGDAM,
+            knockback: Class::FIGKNOCK,
+        }
+    }
+
+    fn sounds(&self) -> SoundSet {
+        SoundSet {
+            swing: "data/sounds/fighter_swing.ogg",
+            fire: "data/sounds/fighter_fire.ogg",
+            ..SoundSet::default()
+        }
+    }
+
+    ///spends a charge to fire a projectile
+    fn special(&self, player: &mut Player, ctx: &mut ScriptMessageContext) {
+        if player.charges > 0 && player.fire_projectile(ctx) {
+            player.charges -= 1;
+        }
+    }
+
+    ///tell the fighter to increase their charge on a sucessful hit
+    fn on_hit(&self, weapon: &Weapon, _target: Handle<Node>, ctx: &mut ScriptContext) {
+        ctx.message_sender.send_to_target(weapon.player,
+            Message::Charges{i: 1}
+        );
+    }
+}
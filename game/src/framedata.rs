@@ -0,0 +1,90 @@
+//this module contains the frame data for melee attacks, fighting-game style:
+// startup frames, where the weapon is swinging but can't hit yet
+// active frames, where the weapon hits anything it touches (once per target)
+// recovery frames, where the weapon is done but the player can't act yet
+// and the debug overlay (F3) that shows where each player is in their attack
+//a "frame" here is one tick of the simulation clock (see clock.rs)
+use crate::*;
+
+#[derive(Debug, Clone, Copy)]
+pub struct FrameData {
+    pub startup: i32,
+    pub active: i32,
+    pub recovery: i32,
+    //frames the whole scene freezes for when the attack connects (global hit-stop, see feedback.rs)
+    pub hitstop: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttackPhase {
+    Startup,
+    Active,
+    Recovery,
+    Over,
+}
+
+impl FrameData {
+    ///total length of the attack in frames
+    pub fn total(&self) -> i32 {
+        self.startup + self.active + self.recovery
+    }
+
+    ///the phase of the attack at a given frame; frames start counting at 1
+    pub fn phase(&self, frame: i32) -> AttackPhase {
+        if frame <= self.startup {
+            AttackPhase::Startup
+        } else if frame <= self.startup + self.active {
+            AttackPhase::Active
+        } else if frame <= self.total() {
+            AttackPhase::Recovery
+        } else {
+            AttackPhase::Over
+        }
+    }
+}
+
+///the F3 overlay listing every player's attack frame data and current frame
+pub struct FrameDataOverlay {
+    pub text: Handle<UiNode>,
+    pub visible: bool,
+}
+
+impl FrameDataOverlay {
+    pub fn new(ui: &mut UserInterface) -> Self {
+        Self {
+            text: create_text_with_background(ui, "", 10.0, 150.0, Color::opaque(255, 255, 255)),
+            visible: false,
+        }
+    }
+
+    pub fn toggle(&mut self, ui: &mut UserInterface) {
+        self.visible = !self.visible;
+        set_text_visibility(ui, self.text, self.visible);
+    }
+
+    ///rebuilds the overlay text from the current state of every player
+    pub fn update(&self, ui: &mut UserInterface, graph: &Graph, players: &[Handle<Node>]) {
+        if !self.visible {
+            return;
+        }
+
+        let mut text = String::new();
+        for (i, handle) in players.iter().enumerate() {
+            if let Some(player) = graph.try_get(*handle).and_then(|n| n.try_get_script::<Player>()) {
+                let frames = player.class.behavior().melee().frames;
+                text += &format!("P{} {:?} {:?}", i + 1, player.class, player.state);
+                if let PlayerState::Attacking(frame) = player.state {
+                    text += &format!(" {:?} {}/{}", frames.phase(frame), frame, frames.total());
+                }
+                text += &format!("\n   startup {} active {} recovery {} hitstop {}\n",
+                    frames.startup, frames.active, frames.recovery, frames.hitstop);
+            }
+        }
+
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+}
@@ -1,16 +1,33 @@
 //this module contains the Plugin script Game, which handles:
 // controller input
 // class selection menu
-// hud 
+// hud (see hud.rs for the widgets themselves)
 // creating the players and starting the game
+// recording and playing back replays (see replay.rs)
+// hosting or joining a LAN game (see net.rs), or a peer-to-peer rollback match (see rollback.rs)
+// spectating, locally or over the LAN (see spectator.rs)
+// CPU bots in the slots nobody's using (see bot.rs)
+// training mode against a dummy (see training.rs)
+// writing every match's events to the telemetry file (see telemetry.rs)
+// the options menu and putting options into effect (see options.rs)
+// picking player profiles in the lobby and adding finished matches to them (see profiles.rs)
+// tournaments: setting one up, playing its sets and going back to the lobby between games (see tournament.rs)
 use crate::*;
+use fyrox::{
+    engine::GraphicsContext,
+    event::{ElementState, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+    window::Fullscreen,
+};
 
 
 pub struct Game {
     pub scene: Handle<Scene>,
     pub gils: Gilrs,
-    pub players: HashMap<g::GamepadId, Handle<Node>>,
-    pub playerclasses: HashMap<g::GamepadId, class::Class>,
+    //players and their classes are keyed by slot (player number - 1);
+    // the gamepad in idList[i] controls slot i
+    pub players: BTreeMap<usize, Handle<Node>>,
+    pub playerclasses: BTreeMap<usize, class::Class>,
     pub idList: Vec::<g::GamepadId>,
     //start_button_handle: Handle<UiNode>,
  
@@ -18,40 +35,85 @@ pub struct Game {
     //look at new() for more info
     pub menu: Vec<Handle<UiNode>>,
 
-    pub id_list: Vec::<GamepadId>,
+    // the fixed-step clock the simulation runs on; see clock.rs
+    pub clock: Clock,
+    // all gameplay randomness goes through here; see rng.rs
+    pub rng: MatchRng,
+    // recording the current match, or playing back an old one
+    pub replay: ReplayMode,
+    // hosting, joining, or playing offline
+    pub net: Net,
+    // client only: our stand-ins for the projectiles the host is simulating, by host id
+    pub remote_projectiles: HashMap<(u32, u32), Handle<Node>>,
+    // peer-to-peer rollback session, if we're playing one
+    pub rollback: Option<RollbackSession>,
+    // true while rollback is replaying ticks it already showed; those don't make sounds again
+    pub resimulating: bool,
+    // the spectator camera and overlays, while we're watching instead of playing
+    pub spectator: Option<Spectator>,
+    // how many bots to add when the match starts (F9 on the menu) and how good they are (F10)
+    pub bot_fill: usize,
+    pub bot_difficulty: Difficulty,
+    // the bots playing in the current match
+    pub bots: Vec<Bot>,
+    // the dummy and hit log, in training mode
+    pub training: Option<Training>,
+    // command line options still to act on once the scene loads; see launch.rs
+    pub launch: Option<LaunchOptions>,
 
-    // first 4 entries are the four "health:" widgets, then their respective strings of x for health bars
-    pub hud: Vec<Handle<UiNode>>,
-    // indicates if on_updtate should check the health bars for players
-    pub phealthchanged: bool,
+    // one hud widget per player, in player number order
+    pub huds: Vec<PlayerHud>,
+    // events published by scripts since the last update; see events.rs
+    pub events: Vec<GameEvent>,
+    // damage, kills, parries etc. for the current match
+    pub stats: MatchStats,
+    // stock limit etc. for the current match, and how it ended
+    pub rules: MatchRules,
+    pub result: Option<MatchResult>,
+    // "Player N wins!" text
+    pub banner: Handle<UiNode>,
+    // F3 overlay showing every player's attack frame data
+    pub frame_overlay: FrameDataOverlay,
+    // F2 overlay drawing hitboxes, facing and player state
+    pub debug: DebugOverlay,
+    // "P1".."P4" next to each player
+    pub tags: PlayerTags,
+    // hit-stop, screen shake and damage numbers
+    pub feedback: Feedback,
+    // keeps the living players in view; see camera.rs
+    pub camera: CameraRig,
+    // every gameplay event, written out for balance analysis
+    pub telemetry: Telemetry,
+    // sound effects, music and volume
+    pub audio: Audio,
+    // the player's saved options, and the menu for changing them (F4); see options.rs
+    pub options: Options,
+    pub options_menu: Option<OptionsMenu>,
+    // local player profiles, which slot picked which, and the lobby's picker
+    pub profiles: Profiles,
+    pub profile_lobby: ProfileLobby,
+    // the tournament being played and its setup screen (F11)
+    pub tournament: Option<TournamentMode>,
+    pub tournament_setup: Option<TournamentSetup>,
     //ctx: UserInterface,
     //HEALTH_TXT: String,
 }
 
 
 impl Game {
-    pub fn new(scene_path: Option<&str>, context: PluginContext) -> Self {
+    pub fn new(scene_path: Option<&str>, context: PluginContext, launch: LaunchOptions) -> Self {
+        init_logging();
+        let options = Options::load();
+
+        //--arena wins over whatever scene the executor or editor asked for
+        let arena = launch.scene_path();
         context
             .async_scene_loader
-            .request(scene_path.unwrap_or("data/scene1.rgs"));
-
-        //create Heads Up Display
-        let color1 = Color::opaque(66, 245, 158); 
-        let color2 = Color::opaque(66, 167, 245);
-        let color3 = Color::opaque(194, 136, 252);
-        let color4 = Color::opaque(250, 135, 215);
-
+            .request(arena.as_deref().or(scene_path).unwrap_or("data/scene1.rgs"));
 
-        let mut hud = Vec::<Handle<UiNode>>::new();
-        hud.push(create_text_with_background(context.user_interface, "health:", 100.0, 100.0, color1.clone()));
-        hud.push(create_text_with_background(context.user_interface, "health:", 300.0, 100.0, color2.clone()));
-        hud.push(create_text_with_background(context.user_interface, "health:", 500.0, 100.0, color3.clone()));
-        hud.push(create_text_with_background(context.user_interface, "health:", 700.0, 100.0, color4.clone()));
-
-        hud.push(create_text_with_background(context.user_interface, "", 175.0, 100.0, color1.clone()));
-        hud.push(create_text_with_background(context.user_interface, "", 375.0, 100.0, color2.clone()));
-        hud.push(create_text_with_background(context.user_interface, "", 575.0, 100.0, color3.clone()));
-        hud.push(create_text_with_background(context.user_interface, "", 775.0, 100.0, color4.clone()));
+        //the huds themselves are made when the game starts; this is just the end of match banner
+        let banner = create_text_with_background(context.user_interface, "", 800.0, 300.0, Color::opaque(255, 255, 255));
+        set_text_visibility(context.user_interface, banner, false);
 
         //create class choice menu
         let mut menu = Vec::<Handle<UiNode>>::new();
@@ -82,22 +144,402 @@ impl Game {
         menu.push(create_player_class_button(context.user_interface, 200.0, 0.0, 200.0, 20.0, "Player 2", Some(VerticalAlignment::Bottom), Option::None));
         menu.push(create_player_class_button(context.user_interface, 400.0, 0.0, 200.0, 20.0, "Player 3", Some(VerticalAlignment::Bottom), Option::None));
         menu.push(create_player_class_button(context.user_interface, 600.0, 0.0, 200.0, 20.0, "Player 4", Some(VerticalAlignment::Bottom), Option::None));
+        //options, under the start button
+        menu.push(create_player_class_button(context.user_interface, 800.0, 70.0, 200.0, 40.0, "Options", Option::None, Some(HorizontalAlignment::Center)));
+        //profile pickers for each player (22 to 25), new profile (26) and the name box (27)
+        let profile_lobby = ProfileLobby::new(context.user_interface, &mut menu);
+
+        let frame_overlay = FrameDataOverlay::new(context.user_interface);
+
+        let net = Net::new(&NetConfig::from_env());
+        let spectator = match net.is_spectator() {
+            true => Some(Spectator::new(context.user_interface)),
+            false => None,
+        };
 
         Self {
             //ctx: context.user_interface,
             scene: Handle::NONE,
             gils: Gilrs::new().unwrap(),
-            players: HashMap::new(),
-            playerclasses: HashMap::new(),
+            players: BTreeMap::new(),
+            playerclasses: BTreeMap::new(),
             idList: Vec::new(),
 
             menu,
             
             
-            id_list: Vec::<GamepadId>::new(),
-            hud,
-            phealthchanged: false,
+            clock: Clock::default(),
+            rng: MatchRng::default(),
+            replay: ReplayMode::Off,
+            net,
+            remote_projectiles: HashMap::new(),
+            rollback: RollbackSession::from_env(),
+            resimulating: false,
+            spectator,
+            bot_fill: launch.bots,
+            bot_difficulty: launch.difficulty,
+            bots: Vec::new(),
+            training: None,
+            huds: Vec::new(),
+            events: Vec::new(),
+            stats: MatchStats::default(),
+            rules: launch.rules.clone().unwrap_or(options.rules.clone()),
+            result: None,
+            banner,
+            frame_overlay,
+            debug: DebugOverlay::default(),
+            tags: PlayerTags::default(),
+            feedback: Feedback::default(),
+            telemetry: Telemetry::from_env(),
+            camera: CameraRig::default(),
+            audio: Audio{volume: options.volume, ..Default::default()},
+            options,
+            options_menu: None,
+            profiles: Profiles::load(),
+            profile_lobby,
+            tournament: None,
+            tournament_setup: None,
             //HEALTH_TXT: "health:".to_string(),
+            launch: Some(launch),
+        }
+    }
+}
+
+impl Game {
+    ///passes a published event on to every system that listens for it
+    pub fn handle_event(&mut self, event: &GameEvent, graph: &Graph, ui: &mut UserInterface) {
+        self.stats.on_event(event);
+        if !self.resimulating {
+            self.telemetry.on_event(event, self.clock.tick);
+            self.audio.on_event(event, graph);
+        }
+
+        if let Some(training) = &mut self.training {
+            training.on_event(event, graph, &self.players, ui);
+        }
+
+        if let GameEvent::PlayerDamaged{attacker, damage, position, ..} = event {
+            self.feedback.hit_stop(hit_stop_length(graph, *attacker));
+            if !self.resimulating {
+                self.feedback.hit(*position, *damage, feedback_tuning(graph, *attacker));
+            }
+        }
+
+        for player in event.players() {
+            if let Some(hud) = self.huds.iter().find(|h| h.player == player) {
+                hud.refresh(ui, graph, &self.stats.get(player));
+            }
+        }
+    }
+
+    ///with a stock limit, the match ends when at most one player has stocks left
+    pub fn check_match_over(&mut self, ui: &mut UserInterface, graph: &Graph) {
+        if self.result.is_some() || self.rules.stocks.is_none() {
+            return;
+        }
+        if self.players.len() < 2 {
+            return;
+        }
+
+        let mut standing = Vec::new();
+        for (slot, handle) in &self.players {
+            if let Some(player) = graph.try_get(*handle).and_then(|n| n.try_get_script::<Player>()) {
+                if player.stocks != Some(0) {
+                    standing.push(*slot);
+                }
+            }
+        }
+        if standing.len() > 1 {
+            return;
+        }
+
+        let result = MatchResult{winner: standing.first().cloned()};
+        let mut text = match result.winner {
+            Some(i) => format!("{} wins!", self.profiles.display_name(i)),
+            None => "Draw!".to_string(),
+        };
+        //tournament games count towards their set; a draw is played again
+        if let Some(mode) = &mut self.tournament {
+            if let Some(side @ 0..=1) = result.winner {
+                mode.tournament.record_game(side);
+                mode.save();
+            }
+            text += "\npress enter to continue";
+        }
+        ui.send_message(TextMessage::text(self.banner, MessageDirection::ToWidget, text));
+        set_text_visibility(ui, self.banner, true);
+        self.telemetry.finish(self.clock.tick, result.winner, true, &self.stats);
+        self.record_profiles(result.winner);
+        self.result = Some(result);
+        self.save_replay();
+    }
+
+    ///adds the match that just finished to the profiles of everyone who picked one
+    fn record_profiles(&mut self, winner: Option<usize>) {
+        //playing back a replay isn't playing it again
+        if matches!(self.replay, ReplayMode::Playback(_)) || self.profiles.chosen.is_empty() {
+            return;
+        }
+        for (slot, handle) in &self.players {
+            if let Some(class) = self.playerclasses.get(slot) {
+                self.profiles.record(*slot, class.clone(), &self.stats.get(*handle), winner == Some(*slot));
+            }
+        }
+        if let Err(e) = self.profiles.save() {
+            error!("couldn't save profiles: {e:?}");
+        }
+    }
+
+    ///the button bindings for a slot: its profile's if it has its own, otherwise the options'
+    pub fn bindings(&self, slot: usize) -> Bindings {
+        self.profiles.get(slot).and_then(|p| p.bindings).unwrap_or(self.options.bindings)
+    }
+
+    ///clears away the match that was played and goes back to the lobby
+    pub fn end_match(&mut self, context: &mut PluginContext) {
+        let graph = &mut context.scenes[self.scene].graph;
+        //removing a player takes their sprite, weapon and chevron with them
+        let mut gone: Vec<Handle<Node>> = graph.pair_iter()
+            .filter(|(_, n)| n.try_get_script::<Projectile>().is_some())
+            .map(|(h, _)| h)
+            .collect();
+        gone.extend(self.players.values().cloned());
+        for handle in gone {
+            if graph.is_valid_handle(handle) {
+                graph.remove_node(handle);
+            }
+        }
+        for hud in self.huds.drain(..) {
+            hud.remove(context.user_interface);
+        }
+        self.players.clear();
+        self.bots.clear();
+        self.stats = MatchStats::default();
+        self.result = None;
+        self.replay = ReplayMode::Off;
+        set_text_visibility(context.user_interface, self.banner, false);
+
+        //and show the class selection menu again
+        for b in &self.menu {
+            context.user_interface.build_ctx()[b.clone()].set_visibility(true);
+        }
+        self.audio.play_music(Music::Menu, graph, context.resource_manager);
+    }
+
+    ///shows the bracket and puts the next set's players on P1 and P2, with the classes they last played
+    pub fn prepare_set(&mut self, ui: &mut UserInterface) {
+        let mode = match &self.tournament {
+            Some(mode) => mode,
+            None => return,
+        };
+        mode.show_bracket(ui, true);
+        if let Some((_, a, b)) = mode.tournament.next_set() {
+            let tournament = &mode.tournament;
+            let index = |entrant: usize| self.profiles.profiles.iter().position(|p| p.name == tournament.entrants[entrant]);
+            self.profiles.chosen = [(0, index(a)), (1, index(b))].into_iter()
+                .filter_map(|(slot, index)| Some((slot, index?)))
+                .collect();
+            self.playerclasses = (0..2)
+                .map(|slot| (slot, self.profiles.get(slot).and_then(|p| p.class.clone()).unwrap_or_default()))
+                .collect();
+            self.profile_lobby.refresh(ui, &self.profiles);
+        }
+    }
+
+    ///starts the next game of the tournament's current set
+    pub fn start_tournament_game(&mut self, context: &mut PluginContext) {
+        let mode = match &self.tournament {
+            Some(mode) => mode,
+            None => return,
+        };
+        if mode.tournament.next_set().is_none() {
+            info!("the tournament is over; F11 closes it");
+            return;
+        }
+        mode.show_bracket(context.user_interface, false);
+        self.rules = mode.tournament.rules.clone();
+        Tournament::seat(&mut self.playerclasses);
+        self.start_match(context, MatchRng::fresh());
+        self.start_telemetry();
+    }
+
+    ///F11 in the lobby: picks up a saved tournament, or opens (or closes) the setup for a new one
+    pub fn toggle_tournament(&mut self, context: &mut PluginContext) {
+        if let Some(setup) = self.tournament_setup.take() {
+            setup.remove(context.user_interface);
+            return;
+        }
+        match self.tournament.take() {
+            //a finished tournament is closed; one still going carries on
+            Some(mode) if mode.tournament.champion().is_some() => mode.remove(context.user_interface),
+            Some(mode) => {
+                info!("finish the tournament first");
+                self.tournament = Some(mode);
+            },
+            None => match Tournament::load().filter(|t| t.champion().is_none()) {
+                Some(tournament) => {
+                    info!("picking the tournament in {} back up", Tournament::PATH);
+                    self.begin_tournament(context, tournament);
+                },
+                None => self.tournament_setup = Some(TournamentSetup::new(context.user_interface, &self.profiles)),
+            },
+        }
+    }
+
+    fn begin_tournament(&mut self, context: &mut PluginContext, tournament: Tournament) {
+        let mode = TournamentMode::new(context.user_interface, tournament);
+        mode.save();
+        self.tournament = Some(mode);
+        //tournament sets are one on one
+        self.bot_fill = 0;
+        self.prepare_set(context.user_interface);
+    }
+
+    ///the handles of every player in the game, in player number order
+    pub fn player_handles(&self) -> Vec<Handle<Node>> {
+        self.players.values().cloned().collect()
+    }
+
+    ///sends an input to the player in a slot, recording it if this match is being recorded
+    pub fn send_input(&mut self, slot: usize, input: PadInput, sender: &ScriptMessageSender) {
+        if let ReplayMode::Recording(replay) = &mut self.replay {
+            replay.record(self.clock.tick, slot, input);
+        }
+        if let Some(training) = &mut self.training {
+            training.record(slot, self.clock.tick, input);
+        }
+        if let Some(player_handle) = self.players.get(&slot) {
+            sender.send_to_target(*player_handle, Message::Controller{event: input});
+        }
+    }
+
+    ///gives bot_fill bots the empty slots with no pad in them, each with a random class
+    pub fn add_bots(&mut self, seed: u64) {
+        let mut picker = MatchRng::new(seed);
+        let classes = [Class::Barbarian, Class::Rogue, Class::Wizard, Class::Fighter];
+        let free: Vec<usize> = (self.idList.len()..4).filter(|s| !self.playerclasses.contains_key(s)).collect();
+        for slot in free.into_iter().take(self.bot_fill) {
+            let class = picker.choose(&classes).cloned().unwrap_or_default();
+            info!("player {} is a {:?} bot", slot + 1, self.bot_difficulty);
+            self.playerclasses.insert(slot, class);
+            self.bots.push(Bot::new(slot, self.bot_difficulty, seed));
+        }
+    }
+
+    ///starts training mode: player 1 as whatever they picked, against a dummy in player 2's slot
+    /// (player 2's pick, if there is one). nobody runs out of stocks, and nothing is recorded
+    pub fn start_training(&mut self, context: &mut PluginContext) {
+        let class = self.playerclasses.get(&0).cloned().unwrap_or_default();
+        let dummy = self.playerclasses.get(&1).cloned().unwrap_or_default();
+        self.playerclasses = BTreeMap::from([(0, class), (1, dummy)]);
+        self.rules = MatchRules::default();
+        self.training = Some(Training::new(context.user_interface, 1));
+        self.start_match(context, MatchRng::fresh());
+        self.replay = ReplayMode::Off;
+    }
+
+    ///starts writing the match that just started to the telemetry file
+    pub fn start_telemetry(&mut self) {
+        let roster = self.players.iter().filter_map(|(slot, handle)| {
+            let class = format!("{:?}", self.playerclasses.get(slot)?);
+            let bot = self.bots.iter().any(|b| b.slot == *slot);
+            Some((*handle, PlayerInfo{slot: *slot, class, bot}))
+        }).collect();
+        self.telemetry.start(self.rng.seed(), &self.rules, roster);
+    }
+
+    ///hides the menu and creates the players and their huds, using the classes in playerclasses.
+    /// the match uses the given rng, and is recorded
+    pub fn start_match(&mut self, context: &mut PluginContext, rng: MatchRng) {
+        for (slot, class) in &self.playerclasses {
+            info!("player {} is {class:?}", slot + 1);
+        }
+        self.audio.play_music(Music::Battle, &mut context.scenes[self.scene].graph, context.resource_manager);
+        let ctx = &mut context.user_interface;
+
+        //hide the class selection menu
+        for b in &self.menu {
+            ctx.build_ctx()[b.clone()].set_visibility(false);
+        }
+
+        self.clock.reset();
+        self.rng = rng;
+        let classes: Vec<(usize, Class)> = self.playerclasses.iter().map(|(s, c)| (*s, c.clone())).collect();
+        self.replay = ReplayMode::Recording(Replay::new(self.rng.seed(), self.rules.clone(), classes.clone()));
+        self.net.send_start(self.rng.seed(), &self.rules, &classes);
+
+        for (slot, class) in classes {
+            create_player(slot, class, context, self);
+        }
+
+        //build a hud for every player, in their team colour and with their profile's name
+        let graph = &context.scenes[self.scene].graph;
+        for (slot, handle) in &self.players {
+            if let Some(player) = graph[*handle].try_get_script::<Player>() {
+                let name = self.profiles.display_name(*slot);
+                let hud = PlayerHud::new(context.user_interface, *handle, slot + 1, &name, player.color);
+                hud.refresh(context.user_interface, graph, &self.stats.get(*handle));
+                self.huds.push(hud);
+            }
+        }
+    }
+
+    ///starts a match that plays back a recorded replay instead of reading the gamepads
+    pub fn start_playback(&mut self, context: &mut PluginContext, replay: Replay) {
+        self.rules = replay.rules.clone();
+        self.playerclasses = replay.players.iter().cloned().collect();
+        self.start_match(context, MatchRng::new(replay.seed));
+        self.replay = ReplayMode::Playback(Playback::new(replay));
+    }
+
+    ///opens the options menu, or closes it and saves the options
+    pub fn toggle_options(&mut self, context: &mut PluginContext) {
+        match self.options_menu.take() {
+            Some(menu) => {
+                menu.remove(context.user_interface);
+                if let Err(e) = self.options.save() {
+                    error!("couldn't save options: {e:?}");
+                }
+            },
+            None => self.options_menu = Some(OptionsMenu::new(context.user_interface, &self.options)),
+        }
+    }
+
+    ///puts changed options into effect
+    pub fn apply_options(&mut self, context: &mut PluginContext) {
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.audio.set_volume(self.options.volume, &mut scene.graph);
+        }
+        if let GraphicsContext::Initialized(graphics) = &context.graphics_context {
+            let fullscreen = self.options.fullscreen.then_some(Fullscreen::Borderless(None));
+            graphics.window.set_fullscreen(fullscreen);
+        }
+        //the match being played keeps its rules
+        if self.players.is_empty() {
+            self.rules = self.options.rules.clone();
+        }
+    }
+
+    ///starts a match someone else set up (the LAN host, or player 1 of a rollback match)
+    pub fn start_remote_match(&mut self, context: &mut PluginContext, seed: u64, rules: MatchRules, players: Vec<(usize, Class)>) {
+        if !self.players.is_empty() {
+            return;
+        }
+        self.rules = rules;
+        self.playerclasses = players.into_iter().collect();
+        self.start_match(context, MatchRng::new(seed));
+        //LAN hosts keep the replay; rollback inputs don't go through send_input, so nothing would be recorded
+        self.replay = ReplayMode::Off;
+    }
+
+    ///saves the match being recorded, if there is one
+    pub fn save_replay(&mut self) {
+        if let ReplayMode::Recording(mut replay) = std::mem::take(&mut self.replay) {
+            replay.length = self.clock.tick;
+            match replay.save(Replay::DEFAULT_PATH) {
+                Ok(()) => info!("saved replay to {}", Replay::DEFAULT_PATH),
+                Err(e) => error!("couldn't save replay: {e:?}"),
+            }
         }
     }
 }
@@ -105,7 +547,10 @@ impl Game {
 impl Plugin for Game {
 
     fn on_deinit(&mut self, _context: PluginContext) {
-        // Do a cleanup here.
+        //don't lose a match that was still going when the game closed
+        self.save_replay();
+        self.telemetry.finish(self.clock.tick, None, false, &self.stats);
+        self.net.leave();
     }
 
     fn update(&mut self, context: &mut PluginContext) {
@@ -134,167 +579,219 @@ impl Plugin for Game {
                     self.idList.push(id);
 
                 },
+                //the options menu is waiting for a button to bind
+                ButtonPressed(button, _) if self.options_menu.as_mut()
+                    .map_or(false, |m| m.on_button(button, &mut self.options, context.user_interface)) => (),
+                //spectators never control anyone
+                _ if self.spectator.is_some() => (),
+                //rollback peers hand their first pad's input to the session, which decides its tick
+                _ if self.rollback.is_some() => if let Some(input) = PadInput::from_gilrs(&event).and_then(|i| self.bindings(0).remap(i)) {
+                    if self.idList.first() == Some(&id) {
+                        if let Some(session) = &mut self.rollback {
+                            session.add_local_input(input);
+                        }
+                    }
+                },
+                //clients send their first pad's input to the host instead of their own players
+                _ if self.net.is_client() => if let Some(input) = PadInput::from_gilrs(&event).and_then(|i| self.bindings(0).remap(i)) {
+                    if self.idList.first() == Some(&id) {
+                        self.net.send_input(input);
+                    }
+                },
+                //during playback the recorded inputs drive the players instead
+                _ if matches!(self.replay, ReplayMode::Playback(_)) => (),
                 //send the controller event to the player
-                _ => if let Some(player_handle) = self.players.get(&id) {
-                    if let Some(message_sender) = &messager {
-                        message_sender.send_to_target(player_handle.clone(), Message::Controller{event});
-                    } else {println!("didn't get messager");}
-                } else {println!("didn't get player handle");}
+                _ => if let Some(input) = PadInput::from_gilrs(&event) {
+                    let slot = self.idList.iter().position(|i| *i == id);
+                    let input = match self.bindings(slot.unwrap_or(0)).remap(input) {
+                        Some(input) => input,
+                        None => continue,
+                    };
+                    match (slot, &messager) {
+                        (Some(slot), Some(message_sender)) => self.send_input(slot, input, message_sender),
+                        (_, None) => warn!("didn't get messager"),
+                        _ => debug!("didn't get player slot"),
+                    }
+                }
 
             }  
         }  
 
-        // changes the number of xs in the health status bar
-        let ctx = &mut context.user_interface;
-        // let health_txt = "health:";
-        //let text = "".to_string();
+        //handle whatever came in over the network
+        let mut start = None;
+        let mut snapshot = None;
+        let taken: Vec<usize> = (0..self.idList.len()).collect();
+        for event in self.net.poll(&taken, self.players.is_empty()) {
+            match event {
+                NetEvent::Joined{slot, class} => {self.playerclasses.insert(slot, class);},
+                NetEvent::Left{slot} => if self.players.is_empty() {
+                    self.playerclasses.remove(&slot);
+                },
+                NetEvent::Input{slot, input} => if let Some(message_sender) = &messager {
+                    self.send_input(slot, input, message_sender);
+                },
+                NetEvent::Start{seed, rules, players} => start = Some((seed, rules, players)),
+                //only the newest one matters
+                NetEvent::Snapshot(s) => snapshot = Some(s),
+            }
+        }
 
-        // updates all player health ui
-        if self.phealthchanged {
-        
-            // for player 1
-            if self.players.len() > 0 {
-                // creates health variable here
-                let mut h: u32 = 10;
+        if let Some(session) = &mut self.rollback {
+            if let Some(s) = session.poll(&self.rules) {
+                start = Some((s.seed, s.rules, s.players));
+            }
+        }
 
-            
+        //hand out everything the scripts published last tick, before this tick's state is saved
+        if let Some(scene) = context.scenes.try_get(self.scene) {
+            let events = std::mem::take(&mut self.events);
+            for event in &events {
+                self.handle_event(event, &scene.graph, context.user_interface);
+            }
+        }
 
-                // gets the player handle from hash map for player 1
-                if let Some(player_script) = self.players.get(&self.id_list[0]) {
-                    // gets the node
-                    let node1 = &mut context.scenes[self.scene].graph[player_script.clone()];
-                    // gets the actual player object
-                    let node2 = node1.script_mut().expect("error").cast_mut::<Player>().expect("error");
-                    // sets health variable to player's health
-                    h = node2.health;
+        //work out whether the scripts run a simulation tick this update; clients don't simulate,
+        // they show what the host sends
+        let mut ticking = false;
+        let mut rollback = self.rollback.take();
+        match &mut rollback {
+            Some(session) if session.is_running() && !self.players.is_empty() => {
+                let graph = &mut context.scenes[self.scene].graph;
+                //the peer's input showed we guessed wrong; go back and play it out again
+                if let Some(state) = session.take_rollback() {
+                    session.resim_target = Some(self.clock.tick);
+                    *context.lag += context.dt * (self.clock.tick - state.tick) as f32;
+                    self.clock.tick = state.tick;
+                    //whatever the scripts published came from the future we're throwing away
+                    self.events.clear();
+                    state.restore(self, graph, context.resource_manager);
                 }
-
-                // creates text variable to be passed into text function
-                let mut text: String = "".to_string();
-                let mut i = 0;
-                // makes the text variable have the number of xs corresponding to health value
-                while i < h {
-                    text = text.to_owned() + "x";
-                    i = i+1;
+                //one tick per engine update, so every tick gets its state saved and its inputs
+                if session.can_advance(self.clock.tick) {
+                    if self.feedback.hitstop > 0 {
+                        self.feedback.hitstop -= 1;
+                        //make sure a resimulation still catches up this frame
+                        if session.resimulating() {
+                            *context.lag += context.dt;
+                        }
+                    } else {
+                        self.resimulating = session.resimulating();
+                        let state = CombatState::capture(self.clock.tick, self, graph);
+                        let inputs = session.begin_tick(self.clock.tick, state);
+                        if let Some(message_sender) = &messager {
+                            for (slot, input) in inputs {
+                                if let Some(player_handle) = self.players.get(&slot) {
+                                    message_sender.send_to_target(*player_handle, Message::Controller{event: input});
+                                }
+                            }
+                        }
+                        session.end_tick(self.clock.tick);
+                        ticking = true;
+                    }
+                }
+            },
+            _ if !self.players.is_empty() && !self.net.is_client() => {
+                let mut run = true;
+                if let ReplayMode::Playback(playback) = &mut self.replay {
+                    playback.fast_forward(context.lag, context.dt);
+                    //a paused replay only moves when stepped, one tick at a time
+                    let step = std::mem::take(&mut playback.step);
+                    run = !playback.paused || step;
                 }
+                //hit-stop eats ticks instead of the scripts
+                if run && self.feedback.hitstop > 0 {
+                    self.feedback.hitstop -= 1;
+                } else if run {
+                    ticking = true;
+                }
+            },
+            _ => (),
+        }
+        self.rollback = rollback;
 
-                ctx.send_message(TextMessage::text(
-                    self.hud[4],
-                    MessageDirection::ToWidget,
-                    text.to_owned(),
-                ));
-                let mut p: Handle<UiNode> = self.hud[4];
-                if let Some(wid) = ctx.build_ctx()[self.hud[4].clone()].cast::<Text>() {
-                    p = wid.parent.clone();
+        //bots look at the scene and press their buttons like anyone else
+        if ticking && !self.bots.is_empty() {
+            if let Some(message_sender) = &messager {
+                let players = self.player_handles();
+                let graph = &context.scenes[self.scene].graph;
+                let mut bots = std::mem::take(&mut self.bots);
+                for bot in &mut bots {
+                    if let Some(me) = self.players.get(&bot.slot).cloned() {
+                        for input in bot.update(me, &players, graph) {
+                            self.send_input(bot.slot, input, message_sender);
+                        }
+                    }
                 }
-                ctx.build_ctx()[p].set_visibility(true);
+                self.bots = bots;
             }
+        }
 
-            // player 2
-            if self.players.len() > 1 {
-                // creates health variable here
-                let mut h: u32 = 10;
-
-                // gets the player handle from hash map for player 2
-                if let Some(player_script) = self.players.get(&self.id_list[1]) {
-                    // gets the node
-                    let node1 = &mut context.scenes[self.scene].graph[player_script.clone()];
-                    // gets the actual player object
-                    let node2 = node1.script().unwrap().cast::<Player>().unwrap();
-                    // sets health variable to player's health
-                    h = node2.health;
+        //the training dummy does whatever it's been set to
+        if ticking {
+            if let (Some(training), Some(message_sender)) = (&mut self.training, &messager) {
+                let inputs = training.dummy_inputs(&self.players, &context.scenes[self.scene].graph);
+                let dummy = training.dummy;
+                for input in inputs {
+                    self.send_input(dummy, input, message_sender);
                 }
+            }
+        }
 
-                // creates text variable to be passed into text function
-                let mut text: String = "".to_string();
-                let mut i = 0;
-                // makes the text variable have the number of xs corresponding to health value
-                while i < h {
-                    text = text.to_owned() + "x";
-                    i = i+1;
-                }
-                ctx.send_message(TextMessage::text(
-                    self.hud[5],
-                    MessageDirection::ToWidget,
-                    text.to_owned(),
-                ));
-                let mut p: Handle<UiNode> = self.hud[5];
-                if let Some(wid) = ctx.build_ctx()[self.hud[5].clone()].cast::<Text>() {
-                    p = wid.parent.clone();
+        //feed the players the recorded inputs for the tick about to run
+        if let ReplayMode::Playback(playback) = &mut self.replay {
+            let until = self.clock.tick + ticking as u32;
+            let inputs = playback.inputs_until(until);
+            if playback.finished(until) {
+                playback.paused = true;
+            }
+            if let Some(message_sender) = &messager {
+                for (slot, input) in inputs {
+                    if let Some(player_handle) = self.players.get(&slot) {
+                        message_sender.send_to_target(*player_handle, Message::Controller{event: input});
+                    }
                 }
-                ctx.build_ctx()[p].set_visibility(true);
             }
+        }
+        self.clock.run(ticking);
+        let frozen = !self.players.is_empty() && !ticking && !self.net.is_client();
 
-            // player 3
-            if self.players.len() > 2 {
-                // creates health variable here
-                let mut h: u32 = 10;
-                // gets the player handle from hash map for player 3
-                if let Some(player_script) = self.players.get(&self.id_list[2]) {
-                    // gets the node
-                    let node1 = &mut context.scenes[self.scene].graph[player_script.clone()];
-                    // gets the actual player object
-                    let node2 = node1.script().unwrap().cast::<Player>().unwrap();
-                    // sets health variable to player's health
-                    h = node2.health;
-                }
+        let ctx = &mut context.user_interface;
 
-                // creates text variable to be passed into text function
-                let mut text: String = "".to_string();
-                let mut i = 0;
-                // makes the text variable have the number of xs corresponding to health value
-                while i < h {
-                    text = text.to_owned() + "x";
-                    i = i+1;
-                }
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            self.feedback.update(scene, ctx, frozen);
+            if let Some(training) = &self.training {
+                training.update(&mut scene.graph, &self.players);
+            }
+            self.audio.update(&mut scene.graph, context.resource_manager);
 
-                ctx.send_message(TextMessage::text(
-                    self.hud[6],
-                    MessageDirection::ToWidget,
-                    text.to_owned(),
-                ));
-                let mut p: Handle<UiNode> = self.hud[6];
-                if let Some(wid) = ctx.build_ctx()[self.hud[6].clone()].cast::<Text>() {
-                    p = wid.parent.clone();
-                }
-                ctx.build_ctx()[p].set_visibility(true);
+            for hud in &self.huds {
+                hud.update_timers(ctx, &scene.graph);
             }
+            self.check_match_over(ctx, &scene.graph);
 
-            // player 4
-            if self.players.len() > 3 {
-                // creates health variable here
-                let mut h = 10;
-                // gets the player handle from hash map for player 4
-                if let Some(player_script) = self.players.get(&self.id_list[3]) {
-                    // gets the node
-                    let node1 = &mut context.scenes[self.scene].graph[player_script.clone()];
-                    // gets the actual player object
-                    let node2 = node1.script().unwrap().cast::<Player>().unwrap();
-                    // sets health variable to player's health
-                    h = node2.health;
-                }
+            let players = self.player_handles();
+            self.frame_overlay.update(ctx, &scene.graph, &players);
 
-                // creates text variable to be passed into text function
-                let mut text: String = "".to_string();
-                let mut i = 0;
-                // makes the text variable have the number of xs corresponding to health value
-                while i < h {
-                    text = text.to_owned() + "x";
-                    i = i+1;
-                }
+            let current = self.camera.center().unwrap_or(Vector2::new(0.0, 0.0));
+            let focus = match &mut self.spectator {
+                Some(spectator) => spectator.update(scene, ctx, &self.players, &self.stats, current),
+                None => None,
+            };
+            self.camera.update(&mut scene.graph, ctx.screen_size(), &players, focus, self.feedback.shake * self.options.shake);
 
-                ctx.send_message(TextMessage::text(
-                    self.hud[7],
-                    MessageDirection::ToWidget,
-                    text.to_owned(),
-                ));
-                let mut p: Handle<UiNode> = self.hud[7];
-                if let Some(wid) = ctx.build_ctx()[self.hud[7].clone()].cast::<Text>() {
-                    p = wid.parent.clone();
+            let hitboxes = self.spectator.as_ref().map_or(false, |s| s.hitboxes);
+            self.debug.update(scene, ctx, &players, hitboxes);
+            self.tags.update(&scene.graph, ctx, &self.players);
+
+            if self.net.is_host() && !self.players.is_empty() {
+                self.net.send_snapshot(&take_snapshot(self.clock.tick, &self.players, &self.stats, &scene.graph));
+            }
+            if let Some(snapshot) = snapshot {
+                apply_snapshot(&snapshot, &self.players, &mut self.stats, &mut self.remote_projectiles, &mut scene.graph, context.resource_manager);
+                for hud in &self.huds {
+                    hud.refresh(ctx, &scene.graph, &self.stats.get(hud.player));
                 }
-                ctx.build_ctx()[p].set_visibility(true);
             }
-            self.phealthchanged = false;
         }
 
         // loop{
@@ -303,14 +800,109 @@ impl Plugin for Game {
         //     }
         // }
         ctx.poll_message();
+
+        //the LAN host or our rollback peer started the match; make the same players they did
+        if let Some((seed, rules, players)) = start {
+            self.start_remote_match(context, seed, rules, players);
+        }
     }
 
     fn on_os_event(
         &mut self,
-        _event: &Event<()>,
-        _context: PluginContext,
+        event: &Event<()>,
+        mut context: PluginContext,
     ) {
-        // Do something on OS event here.
+        //debug keys on the keyboard
+        if let Event::WindowEvent { event: WindowEvent::KeyboardInput { event: input, .. }, .. } = event {
+            //the spectator camera needs to know about releases too, for panning
+            if let (Some(spectator), PhysicalKey::Code(code)) = (&mut self.spectator, input.physical_key) {
+                spectator.on_key(code, input.state == ElementState::Pressed, &self.players, context.user_interface);
+            }
+            if input.state == ElementState::Pressed && !input.repeat {
+                //the options menu takes every key while it's open
+                match (&mut self.options_menu, input.physical_key) {
+                    (Some(_), PhysicalKey::Code(KeyCode::F4 | KeyCode::Escape)) => {
+                        self.toggle_options(&mut context);
+                        return;
+                    },
+                    (Some(menu), PhysicalKey::Code(code)) => {
+                        if menu.on_key(code, &mut self.options, context.user_interface) {
+                            self.apply_options(&mut context);
+                        }
+                        return;
+                    },
+                    (None, PhysicalKey::Code(KeyCode::F4)) => {
+                        self.toggle_options(&mut context);
+                        return;
+                    },
+                    _ => (),
+                }
+                //so does the tournament setup
+                match (&mut self.tournament_setup, input.physical_key) {
+                    (Some(_), PhysicalKey::Code(KeyCode::F11 | KeyCode::Escape)) => {
+                        self.toggle_tournament(&mut context);
+                        return;
+                    },
+                    (Some(setup), PhysicalKey::Code(code)) => {
+                        if let Some(tournament) = setup.on_key(code, &self.profiles, &self.rules, context.user_interface) {
+                            self.toggle_tournament(&mut context);
+                            self.begin_tournament(&mut context, tournament);
+                        }
+                        return;
+                    },
+                    _ => (),
+                }
+                if let (Some(training), PhysicalKey::Code(code)) = (&mut self.training, input.physical_key) {
+                    training.on_key(code, self.clock.tick, context.user_interface);
+                }
+                match input.physical_key {
+                    PhysicalKey::Code(KeyCode::F2) => self.debug.toggle(context.user_interface),
+                    PhysicalKey::Code(KeyCode::F3) => self.frame_overlay.toggle(context.user_interface),
+                    //start or stop spectating locally; LAN spectators stay spectators
+                    PhysicalKey::Code(KeyCode::F8) if !self.net.is_spectator() => {
+                        match self.spectator.take() {
+                            Some(spectator) => spectator.remove(context.user_interface),
+                            None => self.spectator = Some(Spectator::new(context.user_interface)),
+                        }
+                    },
+                    //bots for the next match, from the menu
+                    PhysicalKey::Code(KeyCode::F9) if self.players.is_empty() => {
+                        self.bot_fill = (self.bot_fill + 1) % 4;
+                        info!("bots: {} ({:?})", self.bot_fill, self.bot_difficulty);
+                    },
+                    PhysicalKey::Code(KeyCode::F10) if self.players.is_empty() => {
+                        self.bot_difficulty = self.bot_difficulty.next();
+                        info!("bots: {} ({:?})", self.bot_fill, self.bot_difficulty);
+                    },
+                    PhysicalKey::Code(KeyCode::F11) if self.players.is_empty() && self.rollback.is_none() && !self.net.is_client() => {
+                        self.toggle_tournament(&mut context);
+                    },
+                    //back to the lobby between tournament games
+                    PhysicalKey::Code(KeyCode::Enter) if self.tournament.is_some() && self.result.is_some() => {
+                        self.end_match(&mut context);
+                        self.prepare_set(context.user_interface);
+                    },
+                    PhysicalKey::Code(KeyCode::F6) if self.players.is_empty() && self.rollback.is_none() && !self.net.is_client() => {
+                        self.start_training(&mut context);
+                    },
+                    //play back the last recorded match from the menu
+                    PhysicalKey::Code(KeyCode::F7) if self.players.is_empty() => {
+                        match Replay::load(Replay::DEFAULT_PATH) {
+                            Ok(replay) => self.start_playback(&mut context, replay),
+                            Err(e) => error!("couldn't load replay: {e:?}"),
+                        }
+                    },
+                    code => if let ReplayMode::Playback(playback) = &mut self.replay {
+                        match code {
+                            PhysicalKey::Code(KeyCode::KeyP) => playback.paused = !playback.paused,
+                            PhysicalKey::Code(KeyCode::Period) => playback.step = true,
+                            PhysicalKey::Code(KeyCode::KeyF) => playback.toggle_fast_forward(),
+                            _ => (),
+                        }
+                    },
+                }
+            }
+        }
     }
 
     fn on_ui_message(
@@ -319,6 +911,12 @@ impl Plugin for Game {
         message: &UiMessage,
     ) {
         // Handle UI events here.
+        //keep track of the name being typed for a new profile
+        if let Some(TextMessage::Text(text)) = message.data() {
+            if message.destination() == self.profile_lobby.name_box && message.direction() == MessageDirection::FromWidget {
+                self.profile_lobby.typed = text.clone();
+            }
+        }
         if let Some(ButtonMessage::Click) = message.data() {
             let mut i = 0;
 
@@ -336,59 +934,78 @@ impl Plugin for Game {
                 match i {
 
                     //player 1 class buttons
-                    1 if self.idList.len() > 0 => {self.playerclasses.insert(self.idList[0], Class::Barbarian);},
-                    2 if self.idList.len() > 0 => {self.playerclasses.insert(self.idList[0], Class::Rogue);},
-                    3 if self.idList.len() > 0 => {self.playerclasses.insert(self.idList[0], Class::Wizard);},
-                    4 if self.idList.len() > 0 => {self.playerclasses.insert(self.idList[0], Class::Fighter);},
+                    1 if self.idList.len() > 0 => {self.playerclasses.insert(0, Class::Barbarian);},
+                    2 if self.idList.len() > 0 => {self.playerclasses.insert(0, Class::Rogue);},
+                    3 if self.idList.len() > 0 => {self.playerclasses.insert(0, Class::Wizard);},
+                    4 if self.idList.len() > 0 => {self.playerclasses.insert(0, Class::Fighter);},
 
                     //player 2 class buttons
-                    5 if self.idList.len() > 1 => {self.playerclasses.insert(self.idList[1], Class::Barbarian);},
-                    6 if self.idList.len() > 1 => {self.playerclasses.insert(self.idList[1], Class::Rogue);},
-                    7 if self.idList.len() > 1 => {self.playerclasses.insert(self.idList[1], Class::Wizard);},
-                    8 if self.idList.len() > 1 => {self.playerclasses.insert(self.idList[1], Class::Fighter);},
+                    5 if self.idList.len() > 1 => {self.playerclasses.insert(1, Class::Barbarian);},
+                    6 if self.idList.len() > 1 => {self.playerclasses.insert(1, Class::Rogue);},
+                    7 if self.idList.len() > 1 => {self.playerclasses.insert(1, Class::Wizard);},
+                    8 if self.idList.len() > 1 => {self.playerclasses.insert(1, Class::Fighter);},
+
+                    //tournament sets are one on one; P3 and P4 sit them out
+                    9..=16 if self.tournament.is_some() => (),
 
                     //player 3 class buttons
-                    9 if self.idList.len() > 2 => {self.playerclasses.insert(self.idList[2], Class::Barbarian);},
-                    10 if self.idList.len() > 2 => {self.playerclasses.insert(self.idList[2], Class::Rogue);},
-                    11 if self.idList.len() > 2 => {self.playerclasses.insert(self.idList[2], Class::Wizard);},
-                    12 if self.idList.len() > 2 => {self.playerclasses.insert(self.idList[2], Class::Fighter);},
+                    9 if self.idList.len() > 2 => {self.playerclasses.insert(2, Class::Barbarian);},
+                    10 if self.idList.len() > 2 => {self.playerclasses.insert(2, Class::Rogue);},
+                    11 if self.idList.len() > 2 => {self.playerclasses.insert(2, Class::Wizard);},
+                    12 if self.idList.len() > 2 => {self.playerclasses.insert(2, Class::Fighter);},
 
                     //player 4 class buttons
-                    13 if self.idList.len() > 3 => {self.playerclasses.insert(self.idList[3], Class::Barbarian);},
-                    14 if self.idList.len() > 3 => {self.playerclasses.insert(self.idList[3], Class::Rogue);},
-                    15 if self.idList.len() > 3 => {self.playerclasses.insert(self.idList[3], Class::Wizard);},
-                    16 if self.idList.len() > 3 => {self.playerclasses.insert(self.idList[3], Class::Fighter);},
+                    13 if self.idList.len() > 3 => {self.playerclasses.insert(3, Class::Barbarian);},
+                    14 if self.idList.len() > 3 => {self.playerclasses.insert(3, Class::Rogue);},
+                    15 if self.idList.len() > 3 => {self.playerclasses.insert(3, Class::Wizard);},
+                    16 if self.idList.len() > 3 => {self.playerclasses.insert(3, Class::Fighter);},
 
                     //start button
-                    0 => {
-                        for (player, class) in &self.playerclasses {
-                            println!("{player:?} is {class:?}");
+                    0 if self.rollback.is_some() => {
+                        let class = self.playerclasses.get(&0).cloned().unwrap_or_default();
+                        let rules = self.rules.clone();
+                        let ready = self.rollback.as_mut().and_then(|s| s.ready(class, &rules));
+                        match ready {
+                            Some(s) => self.start_remote_match(context, s.seed, s.rules, s.players),
+                            None => info!("waiting for the other player"),
                         }
-                        let ctx = &mut context.user_interface;
-        
-                        //hide the class selection menu
-                        for b in &self.menu {
-                            ctx.build_ctx()[b.clone()].set_visibility(false);
-                        }
-        
-                        let mut i = 1;
-                        for (id, class) in self.playerclasses.clone() {
-                            create_player(i, class, id, context, self);
-                            i += 1;
+                    },
+                    //spectators wait for the host to start
+                    0 if self.net.is_spectator() => (),
+                    0 if self.net.is_client() => {
+                        //ask to join as whatever our first pad picked; the host starts the match
+                        let class = self.playerclasses.get(&0).cloned().unwrap_or_default();
+                        self.net.join(class);
+                    },
+                    21 => self.toggle_options(context),
+
+                    //a tournament puts the next set's profiles on P1 and P2 itself (see prepare_set)
+                    22..=25 if self.tournament.is_some() => (),
+                    //profile pickers; picking a profile picks the class it last played
+                    22..=25 => {
+                        let slot = i - 22;
+                        self.profiles.cycle(slot);
+                        if let Some(class) = self.profiles.get(slot).and_then(|p| p.class.clone()) {
+                            self.playerclasses.insert(slot, class);
                         }
-        
-                        let ctx = &mut context.user_interface;
-        
-                        for i in 0..self.players.len() {
-                            // makes "health:" visible
-                            ctx.build_ctx()[self.hud[i].clone()].set_visibility(true);
-                            let mut q: Handle<UiNode> = self.hud[i];
-                            if let Some(txt) = ctx.build_ctx()[self.hud[i].clone()].cast::<Text>() {
-                                q = txt.parent.clone();
+                        self.profile_lobby.refresh(context.user_interface, &self.profiles);
+                    },
+                    26 => {
+                        if self.profiles.add(&self.profile_lobby.typed, self.options.bindings) {
+                            if let Err(e) = self.profiles.save() {
+                                error!("couldn't save profiles: {e:?}");
                             }
-                            ctx.build_ctx()[q].set_visibility(true);
+                            info!("added profile {}", self.profile_lobby.typed.trim());
+                        } else {
+                            warn!("type a new name for the profile first");
                         }
-        
+                    },
+                    0 if self.tournament.is_some() => self.start_tournament_game(context),
+                    0 => {
+                        let rng = MatchRng::fresh();
+                        self.add_bots(rng.seed());
+                        self.start_match(context, rng);
+                        self.start_telemetry();
                     },
 
                     _ => (),
@@ -410,8 +1027,31 @@ impl Plugin for Game {
         _path: &Path,
         scene: Handle<Scene>,
         _data: &[u8],
-        _context: &mut PluginContext,
+        context: &mut PluginContext,
     ) {
         self.scene = scene;
+        //the camera stays inside this arena's walls
+        self.camera = CameraRig::for_scene(&context.scenes[scene].graph);
+        self.audio.play_music(Music::Menu, &mut context.scenes[scene].graph, context.resource_manager);
+
+        //skip the lobby if the command line asked to
+        let launch = match self.launch.take() {
+            Some(launch) if launch.autostart() => launch,
+            _ => return,
+        };
+        if let Some(path) = &launch.replay {
+            match Replay::load(path) {
+                Ok(replay) => self.start_playback(context, replay),
+                Err(e) => error!("couldn't load replay {path}: {e:?}"),
+            }
+        } else if self.rollback.is_none() && !self.net.is_client() {
+            self.playerclasses = launch.players;
+            let rng = MatchRng::fresh();
+            self.add_bots(rng.seed());
+            self.start_match(context, rng);
+            self.start_telemetry();
+        } else {
+            warn!("--p1 to --p4 only work for local or hosted matches; pick classes in the lobby");
+        }
      }
 }
\ No newline at end of file
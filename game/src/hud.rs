@@ -0,0 +1,129 @@
+//this module contains the heads up display: one PlayerHud widget per player, showing
+// their name (from their profile, if they picked one)
+// a health bar
+// fighter charges
+// special cooldown progress
+// kills, and stocks left if the match has a stock limit
+// the respawn countdown while dead
+//health, charges, kills and stocks are refreshed whenever a GameEvent involves the player;
+// the cooldown and respawn timers change every frame, so those are read straight off the player.
+use crate::*;
+use fyrox::gui::{stack_panel::StackPanelBuilder, BuildContext, Thickness};
+
+pub struct PlayerHud {
+    pub player: Handle<Node>,
+    pub root: Handle<UiNode>,
+    health_fill: Handle<UiNode>,
+    cooldown_fill: Handle<UiNode>,
+    charges: Handle<UiNode>,
+    score: Handle<UiNode>,
+    respawn: Handle<UiNode>,
+}
+
+impl PlayerHud {
+    pub const WIDTH: f32 = 180.0;
+    const BAR_WIDTH: f32 = 160.0;
+
+    ///builds the hud for player number `num` (starting at 1) in their team colour, headed with their
+    /// name ("Player N", or their profile's)
+    pub fn new(ui: &mut UserInterface, player: Handle<Node>, num: usize, name: &str, color: Color) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let name = TextBuilder::new(WidgetBuilder::new().with_foreground(Brush::Solid(Color::BLACK)))
+            .with_text(name)
+            .build(ctx);
+
+        let (health_bar, health_fill) = Self::bar(ctx, 12.0, color);
+        let (cooldown_bar, cooldown_fill) = Self::bar(ctx, 4.0, Color::opaque(230, 230, 230));
+
+        let charges = TextBuilder::new(WidgetBuilder::new().with_foreground(Brush::Solid(Color::BLACK)))
+            .build(ctx);
+        let score = TextBuilder::new(WidgetBuilder::new().with_foreground(Brush::Solid(Color::BLACK)))
+            .build(ctx);
+        let respawn = TextBuilder::new(WidgetBuilder::new().with_foreground(Brush::Solid(Color::BLACK)))
+            .build(ctx);
+
+        let panel = StackPanelBuilder::new(WidgetBuilder::new()
+            .with_margin(Thickness::uniform(4.0))
+            .with_child(name)
+            .with_child(health_bar)
+            .with_child(cooldown_bar)
+            .with_child(charges)
+            .with_child(score)
+            .with_child(respawn))
+            .build(ctx);
+
+        let root = BorderBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(20.0 + (num as f32 - 1.0) * (Self::WIDTH + 20.0), 20.0))
+            .with_width(Self::WIDTH)
+            .with_background(Brush::Solid(Color::from_rgba(color.r, color.g, color.b, 160)))
+            .with_child(panel))
+            .build(ctx);
+
+        Self {player, root, health_fill, cooldown_fill, charges, score, respawn}
+    }
+
+    //a bar is a dark background with a coloured fill whose width we change
+    fn bar(ctx: &mut BuildContext, height: f32, color: Color) -> (Handle<UiNode>, Handle<UiNode>) {
+        let fill = BorderBuilder::new(WidgetBuilder::new()
+            .with_width(Self::BAR_WIDTH)
+            .with_height(height)
+            .with_horizontal_alignment(HorizontalAlignment::Left)
+            .with_background(Brush::Solid(color)))
+            .build(ctx);
+        let bar = BorderBuilder::new(WidgetBuilder::new()
+            .with_margin(Thickness::uniform(1.0))
+            .with_width(Self::BAR_WIDTH)
+            .with_height(height)
+            .with_background(Brush::Solid(Color::opaque(40, 40, 40)))
+            .with_child(fill))
+            .build(ctx);
+        (bar, fill)
+    }
+
+    ///shows the player's current health, charges, kills and stocks
+    pub fn refresh(&self, ui: &mut UserInterface, graph: &Graph, stats: &PlayerStats) {
+        let player = match graph.try_get(self.player).and_then(|n| n.try_get_script::<Player>()) {
+            Some(player) => player,
+            None => return,
+        };
+
+        let max_health = player.class.behavior().health();
+        let frac = if max_health > 0 {player.health as f32 / max_health as f32} else {0.0};
+        ui.send_message(WidgetMessage::width(self.health_fill, MessageDirection::ToWidget, Self::BAR_WIDTH * frac));
+
+        let charges = if player.charges > 0 {format!("charges: {}", player.charges)} else {String::new()};
+        ui.send_message(TextMessage::text(self.charges, MessageDirection::ToWidget, charges));
+
+        let score = match player.stocks {
+            Some(stocks) => format!("kills: {}   stocks: {}", stats.kills, stocks),
+            None => format!("kills: {}", stats.kills),
+        };
+        ui.send_message(TextMessage::text(self.score, MessageDirection::ToWidget, score));
+    }
+
+    ///updates the per-frame parts of the hud: special cooldown and respawn countdown
+    pub fn update_timers(&self, ui: &mut UserInterface, graph: &Graph) {
+        let player = match graph.try_get(self.player).and_then(|n| n.try_get_script::<Player>()) {
+            Some(player) => player,
+            None => return,
+        };
+
+        let progress = match player.class.behavior().special_cooldown() {
+            Some(cool) => (player.cooldown as f32 / cool as f32).min(1.0),
+            None => 0.0,
+        };
+        ui.send_message(WidgetMessage::width(self.cooldown_fill, MessageDirection::ToWidget, Self::BAR_WIDTH * progress));
+
+        let respawn = match player.state {
+            PlayerState::Dead(_) if player.stocks == Some(0) => "out!".to_string(),
+            PlayerState::Dead(frames) => format!("respawn in {:.1}s", Clock::seconds(frames)),
+            _ => String::new(),
+        };
+        ui.send_message(TextMessage::text(self.respawn, MessageDirection::ToWidget, respawn));
+    }
+
+    pub fn remove(&self, ui: &mut UserInterface) {
+        ui.send_message(WidgetMessage::remove(self.root, MessageDirection::ToWidget));
+    }
+}
@@ -0,0 +1,25 @@
+//this module contains PadInput, the controller input that gets sent to players.
+// gilrs events carry platform codes that can't be saved or made up, so Game::update turns them
+// into PadInputs first; that way recorded replays (and anything else that isn't a real
+// gamepad) can drive a player exactly like a controller does.
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PadInput {
+    Axis(g::Axis, f32),
+    Pressed(g::Button),
+    Released(g::Button),
+}
+
+impl PadInput {
+    ///the PadInput for a gilrs event, if it's one players care about
+    pub fn from_gilrs(event: &g::EventType) -> Option<Self> {
+        match event {
+            AxisChanged(axis, value, _) => Some(PadInput::Axis(*axis, *value)),
+            ButtonPressed(button, _) => Some(PadInput::Pressed(*button)),
+            ButtonReleased(button, _) => Some(PadInput::Released(*button)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//this module contains the command line options the desktop executor passes to the game,
+// for scripting quick repro launches:
+// --arena <name or path>    the scene to load: a name like scene1 means data/scene1.rgs
+// --p1 <class> ... --p4 <class>   skip the lobby and start straight away with these classes
+// --stocks <n>              lives per player (the default comes from the options file)
+// --bots <n>                fill this many empty slots with bots
+// --difficulty <level>      easy, medium or hard bots
+// --replay <path>           play back a replay file instead of playing
+// --width <px> --height <px> --fullscreen --headless   the window; the executor handles these
+//  (fullscreen and vsync otherwise come from the options file; see options.rs)
+//the window options live here too so there's one parser and one --help
+use crate::*;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    pub arena: Option<String>,
+    //classes by slot, to start without the lobby
+    pub players: BTreeMap<usize, Class>,
+    //overrides the rules from the options file
+    pub rules: Option<MatchRules>,
+    pub bots: usize,
+    pub difficulty: Difficulty,
+    pub replay: Option<String>,
+    pub window_size: Option<(u32, u32)>,
+    pub fullscreen: bool,
+    pub headless: bool,
+    //--help was asked for; the executor prints USAGE and quits
+    pub help: bool,
+}
+
+impl LaunchOptions {
+    pub const USAGE: &'static str = "\
+usage: executor [options]
+  --arena <name or path>    scene to load (default data/scene1.rgs)
+  --p1 <class> .. --p4 <class>
+                            start straight away with these classes
+                            (barbarian, rogue, wizard or fighter)
+  --stocks <n>              lives per player (default: from the options)
+  --bots <n>                fill this many empty slots with bots
+  --difficulty <level>      bot difficulty: easy, medium or hard
+  --replay <path>           play back a replay file
+  --width <px> --height <px>
+                            window size
+  --fullscreen              borderless fullscreen
+  --headless                run without a window
+  --help                    show this";
+
+    ///parses the arguments after the program name
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut options = LaunchOptions::default();
+        let mut args = args.into_iter();
+        let (mut width, mut height) = (None, None);
+        while let Some(flag) = args.next() {
+            let mut value = || args.next().ok_or(format!("{flag} needs a value"));
+            match flag.as_str() {
+                "--arena" => options.arena = Some(value()?),
+                "--p1" | "--p2" | "--p3" | "--p4" => {
+                    let slot = flag[3..].parse::<usize>().unwrap_or(1) - 1;
+                    options.players.insert(slot, value()?.parse()?);
+                },
+                "--stocks" => options.rules = Some(MatchRules{stocks: Some(number(&flag, &value()?)?)}),
+                "--bots" => options.bots = number(&flag, &value()?)?,
+                "--difficulty" => options.difficulty = value()?.parse()?,
+                "--replay" => options.replay = Some(value()?),
+                "--width" => width = Some(number(&flag, &value()?)?),
+                "--height" => height = Some(number(&flag, &value()?)?),
+                "--fullscreen" => options.fullscreen = true,
+                "--headless" => options.headless = true,
+                "--help" | "-h" => return Ok(LaunchOptions{help: true, ..Default::default()}),
+                _ => return Err(format!("unknown option {flag}\n{}", Self::USAGE)),
+            }
+        }
+        options.window_size = match (width, height) {
+            (None, None) => None,
+            (Some(width), Some(height)) => Some((width, height)),
+            _ => return Err("--width and --height go together".to_string()),
+        };
+        Ok(options)
+    }
+
+    ///the scene file for --arena: a bare name is looked for in data/
+    pub fn scene_path(&self) -> Option<String> {
+        self.arena.as_ref().map(|arena| {
+            if arena.ends_with(".rgs") {
+                arena.clone()
+            } else {
+                format!("data/{arena}.rgs")
+            }
+        })
+    }
+
+    ///whether the game should skip the lobby
+    pub fn autostart(&self) -> bool {
+        !self.players.is_empty() || self.replay.is_some()
+    }
+}
+
+fn number<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("{flag} needs a number, not {value}"))
+}
+
+impl FromStr for Class {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "barbarian" => Ok(Class::Barbarian),
+            "rogue" => Ok(Class::Rogue),
+            "wizard" => Ok(Class::Wizard),
+            "fighter" => Ok(Class::Fighter),
+            _ => Err(format!("unknown class {s}; try barbarian, rogue, wizard or fighter")),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!("unknown difficulty {s}; try easy, medium or hard")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<LaunchOptions, String> {
+        LaunchOptions::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn classes_go_in_their_slots() {
+        let options = parse("--p1 barbarian --p2 wizard").unwrap();
+        assert_eq!(options.players.len(), 2);
+        assert!(matches!(options.players[&0], Class::Barbarian));
+        assert!(matches!(options.players[&1], Class::Wizard));
+        assert!(options.autostart());
+    }
+
+    #[test]
+    fn no_arguments_means_the_lobby() {
+        let options = parse("").unwrap();
+        assert!(options.players.is_empty());
+        assert!(options.rules.is_none());
+        assert!(!options.autostart());
+    }
+
+    #[test]
+    fn width_needs_height() {
+        assert!(parse("--width 1280").is_err());
+        assert!(parse("--height 720").is_err());
+        assert_eq!(parse("--width 1280 --height 720").unwrap().window_size, Some((1280, 720)));
+    }
+
+    #[test]
+    fn unknown_flags_are_errors() {
+        let error = parse("--p1 rogue --turbo").unwrap_err();
+        assert!(error.contains("--turbo"));
+    }
+
+    #[test]
+    fn stocks_must_be_a_number() {
+        assert!(parse("--stocks x").is_err());
+        assert!(parse("--stocks").is_err());
+        assert_eq!(parse("--stocks 5").unwrap().rules.unwrap().stocks, Some(5));
+    }
+
+    #[test]
+    fn help_isnt_an_error() {
+        assert!(parse("--help").unwrap().help);
+        assert!(parse("--p1 rogue -h").unwrap().help);
+        assert!(!parse("--p1 rogue").unwrap().help);
+    }
+}
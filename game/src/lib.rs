@@ -1,7 +1,7 @@
 //! Game project
 #![allow(nonstandard_style)]
 use std::{
-    collections::HashMap,
+    collections::{HashMap, BTreeMap},
     vec::Vec,
     path::Path,
 };
@@ -29,7 +29,7 @@ use fyrox::{
         brush::Brush,
         button::{ButtonBuilder, ButtonMessage},
         core::{color::Color, algebra::UnitQuaternion},
-        widget::WidgetBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
         border::BorderBuilder, 
         message::{UiMessage, MessageDirection}, 
         text::{TextBuilder, TextMessage, Text},
@@ -50,6 +50,8 @@ use fyrox::{
     },
 };
 
+use log::{error, warn, info, debug, trace};
+
 use gilrs as g;
 use gilrs::{
     EventType::*, 
@@ -64,12 +66,46 @@ pub mod player;
 pub mod projectile;
 pub mod game;
 pub mod weapon;
+pub mod behavior;
+pub mod barbarian;
+pub mod rogue;
+pub mod wizard;
+pub mod fighter;
+pub mod state;
+pub mod framedata;
+pub mod feedback;
+pub mod vfx;
+pub mod audio;
+pub mod hud;
+pub mod rules;
+pub mod events;
+pub mod stats;
+pub mod input;
+pub mod replay;
+pub mod rng;
+pub mod clock;
+pub mod net;
+pub mod rollback;
+pub mod spectator;
+pub mod camera;
+pub mod bot;
+pub mod training;
+pub mod debug;
+pub mod logging;
+pub mod telemetry;
+pub mod launch;
+pub mod options;
+pub mod palette;
+pub mod profiles;
+pub mod tournament;
+pub mod save;
 
 use messages::{
     Message,
-    Message::{Hit, Controller, Parried, Attack, Start_Parry, Charges, },
+    Message::{Hit, Controller, Parried, Attack, Start_Parry, Charges, Killed, },
 };
 use class::Class;
+use behavior::{ClassBehavior, MeleeStats};
 
 use create::*;
     // create_text_with_background,
@@ -78,6 +114,62 @@ use create::*;
 
 use player::*;
 
+use state::*;
+
+use framedata::*;
+
+use feedback::*;
+
+use vfx::*;
+
+use audio::*;
+
+use hud::*;
+
+use rules::*;
+
+use events::*;
+
+use stats::*;
+
+use input::*;
+
+use replay::*;
+
+use rng::*;
+
+use clock::*;
+
+use net::*;
+
+use rollback::*;
+
+use spectator::*;
+
+use camera::*;
+
+use bot::*;
+
+use training::*;
+
+use debug::*;
+
+use logging::*;
+
+use telemetry::*;
+
+use launch::*;
+
+use options::*;
+
+use palette::*;
+
+use profiles::*;
+
+use tournament::*;
+
+use save::*;
+
 use projectile::*;
 
 use game::*;
@@ -92,7 +184,11 @@ use weapon::*;
 
 
 
-pub struct GameConstructor;
+//the executor fills in `launch` from its command line; everything else uses the defaults
+#[derive(Default)]
+pub struct GameConstructor {
+    pub launch: LaunchOptions,
+}
 
 impl PluginConstructor for GameConstructor {
     fn register(&self, context: PluginRegistrationContext) {
@@ -103,7 +199,7 @@ impl PluginConstructor for GameConstructor {
     }
 
     fn create_instance(&self, scene_path: Option<&str>, context: PluginContext) -> Box<dyn Plugin> {
-        Box::new(Game::new(scene_path, context))
+        Box::new(Game::new(scene_path, context, self.launch.clone()))
     }
 }
 
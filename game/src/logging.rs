@@ -0,0 +1,113 @@
+//this module contains the logger behind the log crate's macros (error!, warn!, info!, debug!, trace!).
+//every message is tagged with its level and target; the target is the module it came from
+// (e.g. ShapeFights::net), and the crate name is left off when printing.
+//how much gets printed comes from SHAPEFIGHTS_LOG, a comma separated list of a default level and
+// per-module levels, e.g. SHAPEFIGHTS_LOG=warn,net=debug,rollback=trace
+//without it, everything at info and above is printed
+use log::{LevelFilter, Log, Metadata, Record};
+
+pub struct Logger {
+    default: LevelFilter,
+    //module name (without the crate) and the level for it
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Logger {
+    ///parses a SHAPEFIGHTS_LOG style spec; anything that doesn't parse is skipped
+    pub fn parse(spec: &str) -> Self {
+        let mut logger = Logger{default: LevelFilter::Info, modules: Vec::new()};
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.split_once('=') {
+                Some((module, level)) => match level.trim().parse() {
+                    Ok(level) => logger.modules.push((module.trim().to_string(), level)),
+                    Err(_) => eprintln!("bad log level in SHAPEFIGHTS_LOG: {part}"),
+                },
+                None => match part.parse() {
+                    Ok(level) => logger.default = level,
+                    Err(_) => eprintln!("bad log level in SHAPEFIGHTS_LOG: {part}"),
+                },
+            }
+        }
+        logger
+    }
+
+    //the level for a target, from the most specific module that matches it
+    fn level(&self, target: &str) -> LevelFilter {
+        let target = short_target(target);
+        self.modules.iter()
+            .filter(|(module, _)| target == module.as_str() || target.starts_with(&format!("{module}::")))
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules.iter().map(|(_, level)| *level).fold(self.default, std::cmp::max)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{:<5} {}] {}", record.level(), short_target(record.target()), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+//a target without the crate name in front
+fn short_target(target: &str) -> &str {
+    target.split_once("::").map_or(target, |(_, rest)| rest)
+}
+
+///installs the logger, reading SHAPEFIGHTS_LOG. only the first call does anything
+pub fn init_logging() {
+    let logger = Logger::parse(&std::env::var("SHAPEFIGHTS_LOG").unwrap_or_default());
+    let max = logger.max_level();
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_info() {
+        let logger = Logger::parse("");
+        assert_eq!(logger.level("ShapeFights::net"), LevelFilter::Info);
+        assert_eq!(logger.max_level(), LevelFilter::Info);
+    }
+
+    #[test]
+    fn parses_a_default_and_module_levels() {
+        let logger = Logger::parse("warn, net=debug,rollback=trace");
+        assert_eq!(logger.level("ShapeFights::game"), LevelFilter::Warn);
+        assert_eq!(logger.level("ShapeFights::net"), LevelFilter::Debug);
+        assert_eq!(logger.level("ShapeFights::rollback"), LevelFilter::Trace);
+        assert_eq!(logger.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn skips_parts_that_dont_parse() {
+        let logger = Logger::parse("loud,net=verbose,,audio=error");
+        assert_eq!(logger.level("ShapeFights::game"), LevelFilter::Info);
+        assert_eq!(logger.level("ShapeFights::net"), LevelFilter::Info);
+        assert_eq!(logger.level("ShapeFights::audio"), LevelFilter::Error);
+    }
+
+    #[test]
+    fn the_most_specific_module_wins() {
+        let logger = Logger::parse("error,net=warn,net::lobby=trace");
+        assert_eq!(logger.level("ShapeFights::net"), LevelFilter::Warn);
+        assert_eq!(logger.level("ShapeFights::net::lobby"), LevelFilter::Trace);
+        assert_eq!(logger.level("ShapeFights::net::snapshot"), LevelFilter::Warn);
+        //a module name is only a prefix at a :: boundary
+        assert_eq!(logger.level("ShapeFights::network"), LevelFilter::Error);
+    }
+}
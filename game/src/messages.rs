@@ -1,6 +1,6 @@
 use fyrox::scene::node::Node;
 use crate::{Handle, Vector3};
-use gilrs::ev::EventType;
+use crate::input::PadInput;
 
 pub enum Message {
     Hit {
@@ -9,7 +9,7 @@ pub enum Message {
         sender: Handle<Node>,
     },
     Controller {
-        event: EventType,
+        event: PadInput,
 
     },
     Parried {
@@ -27,5 +27,9 @@ pub enum Message {
     //when a player recieves this, they change their "charges" field by the amount inside
     Charges{
         i: i32
-    }
+    },
+    //from a player to the owner of the weapon that just killed them
+    Killed{
+
+    },
 }
\ No newline at end of file
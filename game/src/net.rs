@@ -0,0 +1,522 @@
+//this module contains local-network multiplayer with a dedicated host.
+//the host runs the real simulation; clients only send their controller input and show what the
+// host tells them. everything goes over UDP:
+// a client picks a class and presses start, which sends Join to the host until it gets Welcome
+// the host puts the client in a free slot, and the client's player is created when the host
+//  starts the match (Start carries every slot's class)
+// the client sends its PadInputs; the host feeds them through Game::send_input like a local pad
+// every frame the host sends a Snapshot with every player's position, state, health and stats and
+//  the projectiles in flight, and the client copies it onto its own scene
+// spectators send Watch instead of Join; they get no slot, but are sent Start (even mid-match)
+//  and every Snapshot, and keep sending Watch so the host knows they're still there
+//the address to host on, join or spectate comes from the SHAPEFIGHTS_HOST / SHAPEFIGHTS_JOIN /
+// SHAPEFIGHTS_SPECTATE environment variables, so executors on one machine can play over loopback:
+// SHAPEFIGHTS_HOST=127.0.0.1:7777 cargo run --package executor
+// SHAPEFIGHTS_JOIN=127.0.0.1:7777 cargo run --package executor
+// SHAPEFIGHTS_SPECTATE=127.0.0.1:7777 cargo run --package executor
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::ErrorKind,
+    net::{SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+pub const DEFAULT_PORT: u16 = 7777;
+//biggest datagram we'll read; a four player snapshot is well under this
+const MAX_PACKET: usize = 4096;
+//how often a client asks again to join while waiting for Welcome
+const JOIN_RETRY: Duration = Duration::from_secs(1);
+//how often a spectator tells the host it's still watching
+const WATCH_RETRY: Duration = Duration::from_secs(1);
+//clients the host hasn't heard from in this long are dropped
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClientMsg {
+    Join{ class: Class },
+    Input{ input: PadInput },
+    //watch the match as a spectator, without a slot
+    Watch,
+    Leave,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HostMsg {
+    Welcome{ slot: usize },
+    //the host took us on as a spectator
+    Watching,
+    //the match started; (slot, class) for every player
+    Start{ seed: u64, rules: MatchRules, players: Vec<(usize, Class)> },
+    Snapshot(Snapshot),
+    //the host is full or the match already started
+    Refused,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub slot: usize,
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    pub facing: [f32; 3],
+    pub state: PlayerState,
+    pub health: u32,
+    pub charges: i32,
+    pub stocks: Option<u32>,
+    pub visible: bool,
+    pub weapon_visible: bool,
+    //rotation of the weapon around the z axis
+    pub weapon_angle: f32,
+    //so clients and spectators can show stats without seeing the events themselves
+    pub stats: PlayerStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectileSnapshot {
+    //the host's handle (index and generation), so clients can tell projectiles apart;
+    // the index alone gets reused as soon as an old projectile is removed
+    pub id: (u32, u32),
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub tick: u32,
+    pub players: Vec<PlayerSnapshot>,
+    pub projectiles: Vec<ProjectileSnapshot>,
+}
+
+///where this game sits on the network
+#[derive(Debug, Clone)]
+pub enum NetConfig {
+    Offline,
+    Host(SocketAddr),
+    Join(SocketAddr),
+    Spectate(SocketAddr),
+}
+
+impl NetConfig {
+    ///reads SHAPEFIGHTS_HOST / SHAPEFIGHTS_JOIN / SHAPEFIGHTS_SPECTATE
+    pub fn from_env() -> Self {
+        let parse = |var: &str| std::env::var(var).ok().and_then(|addr| {
+            match addr.parse() {
+                Ok(addr) => Some(addr),
+                Err(e) => {warn!("bad address in {var}: {e}"); None},
+            }
+        });
+        if let Some(addr) = parse("SHAPEFIGHTS_HOST") {
+            NetConfig::Host(addr)
+        } else if let Some(addr) = parse("SHAPEFIGHTS_JOIN") {
+            NetConfig::Join(addr)
+        } else if let Some(addr) = parse("SHAPEFIGHTS_SPECTATE") {
+            NetConfig::Spectate(addr)
+        } else {
+            NetConfig::Offline
+        }
+    }
+}
+
+pub struct RemoteClient {
+    pub addr: SocketAddr,
+    pub slot: usize,
+    pub class: Class,
+    last_heard: Instant,
+}
+
+///something that came in over the network that Game has to act on
+pub enum NetEvent {
+    //host: a client joined and wants this slot and class
+    Joined{ slot: usize, class: Class },
+    //host: a remote player's input
+    Input{ slot: usize, input: PadInput },
+    //host: a client left or timed out
+    Left{ slot: usize },
+    //client: the host started the match
+    Start{ seed: u64, rules: MatchRules, players: Vec<(usize, Class)> },
+    //client: the latest state of the match
+    Snapshot(Snapshot),
+}
+
+pub enum Net {
+    Offline,
+    Host {
+        socket: UdpSocket,
+        clients: Vec<RemoteClient>,
+        //spectators, and when we last heard from them
+        spectators: Vec<(SocketAddr, Instant)>,
+        //the Start of the match in progress, for spectators who turn up late
+        started: Option<HostMsg>,
+    },
+    Client {
+        socket: UdpSocket,
+        host: SocketAddr,
+        //our slot, once the host has welcomed us
+        slot: Option<usize>,
+        //the class we asked to join as, and when we last asked
+        joining: Option<(Class, Instant)>,
+        //spectators only: when we last sent Watch
+        watching: Option<Instant>,
+    },
+}
+
+impl Default for Net {
+    fn default() -> Self {
+        Net::Offline
+    }
+}
+
+impl Net {
+    pub fn new(config: &NetConfig) -> Self {
+        let result = match config {
+            NetConfig::Offline => return Net::Offline,
+            NetConfig::Host(addr) => UdpSocket::bind(addr).map(|socket| {
+                Net::Host{socket, clients: Vec::new(), spectators: Vec::new(), started: None}
+            }),
+            NetConfig::Join(host) | NetConfig::Spectate(host) => {
+                //let the os pick our port, on the same kind of address as the host
+                let local: SocketAddr = if host.is_ipv4() {"0.0.0.0:0"} else {"[::]:0"}.parse().unwrap();
+                //spectators start asking to watch straight away
+                let watching = match config {
+                    NetConfig::Spectate(_) => Some(Instant::now() - WATCH_RETRY),
+                    _ => None,
+                };
+                UdpSocket::bind(local).map(|socket| Net::Client{socket, host: *host, slot: None, joining: None, watching})
+            },
+        };
+        match result {
+            Ok(net) => {
+                if let Some(socket) = net.socket() {
+                    if let Err(e) = socket.set_nonblocking(true) {
+                        error!("couldn't make socket non-blocking: {e}");
+                        return Net::Offline;
+                    }
+                }
+                net
+            },
+            Err(e) => {
+                warn!("couldn't open network socket, playing offline: {e}");
+                Net::Offline
+            },
+        }
+    }
+
+    fn socket(&self) -> Option<&UdpSocket> {
+        match self {
+            Net::Offline => None,
+            Net::Host{socket, ..} | Net::Client{socket, ..} => Some(socket),
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self, Net::Host{..})
+    }
+
+    pub fn is_client(&self) -> bool {
+        matches!(self, Net::Client{..})
+    }
+
+    pub fn is_spectator(&self) -> bool {
+        matches!(self, Net::Client{watching: Some(_), ..})
+    }
+
+    ///client: asks the host for a slot, playing as `class`
+    pub fn join(&mut self, class: Class) {
+        if let Net::Client{joining, ..} = self {
+            *joining = Some((class, Instant::now() - JOIN_RETRY));
+        }
+    }
+
+    ///client: sends an input to the host
+    pub fn send_input(&self, input: PadInput) {
+        if let Net::Client{socket, host, slot: Some(_), ..} = self {
+            send(socket, *host, &ClientMsg::Input{input});
+        }
+    }
+
+    ///host: tells every client the match has started
+    pub fn send_start(&mut self, seed: u64, rules: &MatchRules, players: &[(usize, Class)]) {
+        if let Net::Host{socket, clients, spectators, started} = self {
+            let msg = HostMsg::Start{seed, rules: rules.clone(), players: players.to_vec()};
+            for addr in clients.iter().map(|c| c.addr).chain(spectators.iter().map(|s| s.0)) {
+                send(socket, addr, &msg);
+            }
+            *started = Some(msg);
+        }
+    }
+
+    ///host: sends the state of the match to every client
+    pub fn send_snapshot(&self, snapshot: &Snapshot) {
+        if let Net::Host{socket, clients, spectators, ..} = self {
+            let msg = HostMsg::Snapshot(snapshot.clone());
+            for addr in clients.iter().map(|c| c.addr).chain(spectators.iter().map(|s| s.0)) {
+                send(socket, addr, &msg);
+            }
+        }
+    }
+
+    pub fn leave(&self) {
+        if let Net::Client{socket, host, ..} = self {
+            send(socket, *host, &ClientMsg::Leave);
+        }
+    }
+
+    ///reads everything that arrived since last frame. `taken` is the slots already in use,
+    /// so the host knows where it can put new clients; `open` is whether it still takes them
+    pub fn poll(&mut self, taken: &[usize], open: bool) -> Vec<NetEvent> {
+        let mut events = Vec::new();
+        match self {
+            Net::Offline => (),
+            Net::Host{socket, clients, spectators, started} => {
+                while let Some((msg, addr)) = recv::<ClientMsg>(socket) {
+                    //spectators only ever send Watch and Leave
+                    if let Some(i) = spectators.iter().position(|s| s.0 == addr) {
+                        match msg {
+                            ClientMsg::Leave => {
+                                info!("spectator {addr} left");
+                                spectators.remove(i);
+                            },
+                            _ => spectators[i].1 = Instant::now(),
+                        }
+                        continue;
+                    }
+                    let known = clients.iter().position(|c| c.addr == addr);
+                    if let Some(i) = known {
+                        clients[i].last_heard = Instant::now();
+                    }
+                    match (msg, known) {
+                        //a Join we've already answered; our Welcome probably got lost
+                        (ClientMsg::Join{..}, Some(i)) => send(socket, addr, &HostMsg::Welcome{slot: clients[i].slot}),
+                        (ClientMsg::Join{class}, None) => {
+                            let used: Vec<usize> = taken.iter().cloned().chain(clients.iter().map(|c| c.slot)).collect();
+                            match (0..4).find(|s| !used.contains(s)) {
+                                Some(slot) if open => {
+                                    info!("{addr} joined as player {}", slot + 1);
+                                    clients.push(RemoteClient{addr, slot, class: class.clone(), last_heard: Instant::now()});
+                                    send(socket, addr, &HostMsg::Welcome{slot});
+                                    events.push(NetEvent::Joined{slot, class});
+                                },
+                                _ => send(socket, addr, &HostMsg::Refused),
+                            }
+                        },
+                        (ClientMsg::Watch, None) => {
+                            info!("{addr} is spectating");
+                            spectators.push((addr, Instant::now()));
+                            send(socket, addr, &HostMsg::Watching);
+                            if let Some(start) = started {
+                                send(socket, addr, start);
+                            }
+                        },
+                        (ClientMsg::Input{input}, Some(i)) => events.push(NetEvent::Input{slot: clients[i].slot, input}),
+                        (ClientMsg::Leave, Some(i)) => {
+                            info!("{addr} left");
+                            events.push(NetEvent::Left{slot: clients.remove(i).slot});
+                        },
+                        //input from someone who never joined
+                        _ => (),
+                    }
+                }
+                //drop anyone who's gone quiet
+                clients.retain(|c| {
+                    let alive = c.last_heard.elapsed() < CLIENT_TIMEOUT;
+                    if !alive {
+                        info!("{} timed out", c.addr);
+                        events.push(NetEvent::Left{slot: c.slot});
+                    }
+                    alive
+                });
+                spectators.retain(|(addr, last_heard)| {
+                    let alive = last_heard.elapsed() < CLIENT_TIMEOUT;
+                    if !alive {
+                        info!("spectator {addr} timed out");
+                    }
+                    alive
+                });
+            },
+            Net::Client{socket, host, slot, joining, watching} => {
+                if let Some(asked) = watching {
+                    if asked.elapsed() >= WATCH_RETRY {
+                        send(socket, *host, &ClientMsg::Watch);
+                        *asked = Instant::now();
+                    }
+                }
+                //keep asking until the host answers
+                if let Some((class, asked)) = joining {
+                    if asked.elapsed() >= JOIN_RETRY {
+                        send(socket, *host, &ClientMsg::Join{class: class.clone()});
+                        *asked = Instant::now();
+                    }
+                }
+                while let Some((msg, addr)) = recv::<HostMsg>(socket) {
+                    if addr != *host {
+                        continue;
+                    }
+                    match msg {
+                        HostMsg::Welcome{slot: s} => {
+                            info!("joined as player {}", s + 1);
+                            *slot = Some(s);
+                            *joining = None;
+                        },
+                        HostMsg::Watching => info!("spectating"),
+                        HostMsg::Refused => {
+                            warn!("the host refused to let us join");
+                            *joining = None;
+                        },
+                        HostMsg::Start{seed, rules, players} => events.push(NetEvent::Start{seed, rules, players}),
+                        HostMsg::Snapshot(snapshot) => events.push(NetEvent::Snapshot(snapshot)),
+                    }
+                }
+            },
+        }
+        events
+    }
+}
+
+fn send<T: Serialize>(socket: &UdpSocket, addr: SocketAddr, msg: &T) {
+    match bincode::serialize(msg) {
+        Ok(bytes) => {
+            if let Err(e) = socket.send_to(&bytes, addr) {
+                warn!("couldn't send to {addr}: {e}");
+            }
+        },
+        Err(e) => error!("couldn't encode network message: {e}"),
+    }
+}
+
+//the next datagram that decodes as a T, if one has arrived
+fn recv<T: for<'de> Deserialize<'de>>(socket: &UdpSocket) -> Option<(T, SocketAddr)> {
+    let mut buf = [0u8; MAX_PACKET];
+    loop {
+        match socket.recv_from(&mut buf) {
+            Ok((len, addr)) => match bincode::deserialize(&buf[..len]) {
+                Ok(msg) => return Some((msg, addr)),
+                //garbage or a different version of the game; skip it
+                Err(_) => continue,
+            },
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return None,
+            //on windows an earlier send to a closed port shows up here; ignore it
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+            Err(e) => {
+                error!("network error: {e}");
+                return None;
+            },
+        }
+    }
+}
+
+///host: captures the state of the match for clients
+pub fn take_snapshot(tick: u32, players: &BTreeMap<usize, Handle<Node>>, stats: &MatchStats, graph: &Graph) -> Snapshot {
+    let mut snapshot = Snapshot{tick, players: Vec::new(), projectiles: Vec::new()};
+
+    for (slot, handle) in players {
+        let node = match graph.try_get(*handle) {
+            Some(node) => node,
+            None => continue,
+        };
+        let player = match node.try_get_script::<Player>() {
+            Some(player) => player,
+            None => continue,
+        };
+        let position = node.global_position();
+        let velocity = node.cast::<RigidBody>().map_or(Vector2::new(0.0, 0.0), |b| b.lin_vel());
+        let (weapon_visible, weapon_angle) = match graph.try_get(player.weapon) {
+            Some(weapon) => (weapon.visibility(), weapon.local_transform().rotation().euler_angles().2),
+            None => (false, 0.0),
+        };
+        snapshot.players.push(PlayerSnapshot{
+            slot: *slot,
+            position: [position.x, position.y],
+            velocity: [velocity.x, velocity.y],
+            facing: [player.facing.x, player.facing.y, player.facing.z],
+            state: player.state.clone(),
+            health: player.health,
+            charges: player.charges,
+            stocks: player.stocks,
+            visible: node.visibility(),
+            weapon_visible,
+            weapon_angle,
+            stats: stats.get(*handle),
+        });
+    }
+
+    for (handle, node) in graph.pair_iter() {
+        if let Some(projectile) = node.try_get_script::<Projectile>() {
+            if projectile.hit {
+                continue;
+            }
+            let position = node.global_position();
+            let velocity = node.cast::<RigidBody>().map_or(Vector2::new(0.0, 0.0), |b| b.lin_vel());
+            snapshot.projectiles.push(ProjectileSnapshot{
+                id: (handle.index(), handle.generation()),
+                position: [position.x, position.y],
+                velocity: [velocity.x, velocity.y],
+            });
+        }
+    }
+
+    snapshot
+}
+
+///client: copies a snapshot from the host onto our scene. `projectiles` maps the host's
+/// projectile ids to the stand-ins we made for them
+pub fn apply_snapshot(
+    snapshot: &Snapshot,
+    players: &BTreeMap<usize, Handle<Node>>,
+    stats: &mut MatchStats,
+    projectiles: &mut HashMap<(u32, u32), Handle<Node>>,
+    graph: &mut Graph,
+    resource_manager: &ResourceManager,
+) {
+    for snap in &snapshot.players {
+        let handle = match players.get(&snap.slot) {
+            Some(handle) => *handle,
+            None => continue,
+        };
+        stats.players.insert(handle, snap.stats.clone());
+        let weapon = match graph.try_get_mut(handle).and_then(|n| n.try_get_script_mut::<Player>()) {
+            Some(player) => {
+                //set directly, not through transition(); the host already ran the state machine
+                player.state = snap.state.clone();
+                player.health = snap.health;
+                player.charges = snap.charges;
+                player.stocks = snap.stocks;
+                player.facing = Vector3::new(snap.facing[0], snap.facing[1], snap.facing[2]);
+                player.weapon
+            },
+            None => continue,
+        };
+        let node = &mut graph[handle];
+        node.local_transform_mut().set_position(Vector3::new(snap.position[0], snap.position[1], 0.0));
+        node.set_visibility(snap.visible);
+        if let Some(body) = node.cast_mut::<RigidBody>() {
+            body.set_lin_vel(Vector2::new(snap.velocity[0], snap.velocity[1]));
+        }
+        Player::update_look(Vector3::new(snap.facing[0], snap.facing[1], snap.facing[2]), node);
+
+        if let Some(weapon) = graph.try_get_mut(weapon) {
+            weapon.set_visibility(snap.weapon_visible);
+            weapon.local_transform_mut().set_rotation(
+                UnitQuaternion::from_axis_angle(&Vector3::z_axis(), snap.weapon_angle));
+        }
+    }
+
+    //make stand-ins for new projectiles, move the ones we have, and remove the ones that are gone
+    let live: Vec<(u32, u32)> = snapshot.projectiles.iter().map(|p| p.id).collect();
+    projectiles.retain(|id, handle| {
+        let keep = live.contains(id);
+        if !keep && graph.is_valid_handle(*handle) {
+            graph.remove_node(*handle);
+        }
+        keep
+    });
+    for snap in &snapshot.projectiles {
+        let position = Vector3::new(snap.position[0], snap.position[1], 0.0);
+        let handle = *projectiles.entry(snap.id).or_insert_with(|| create_projectile_standin(graph, resource_manager));
+        if let Some(node) = graph.try_get_mut(handle) {
+            node.local_transform_mut().set_position(position);
+            if let Some(body) = node.cast_mut::<RigidBody>() {
+                body.set_lin_vel(Vector2::new(snap.velocity[0], snap.velocity[1]));
+            }
+        }
+    }
+}
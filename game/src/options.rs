@@ -0,0 +1,295 @@
+//this module contains the player's options, saved to options.ron next to the game
+// (the editor's settings.ron is the editor's own, this file is just for players):
+// master, music and sound effect volume
+// fullscreen and vsync (the executor reads these when it makes the window; vsync only changes on restart)
+// the default match rules
+// how hard the screen shakes
+// the colour palette and emblems/outlines, for colour-blind players (see palette.rs)
+// which pad buttons attack, use the special and parry
+//and the options menu, opened with F4 or the Options button in the lobby:
+// up/down picks a row, left/right changes it, enter rebinds a button (press the new one on any pad),
+// F4 or escape closes the menu and saves
+use crate::*;
+use fyrox::keyboard::KeyCode;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+///which pad button does what. players always see the default buttons (see player.rs);
+/// remap translates whatever the player bound into those before it goes anywhere
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    pub attack: g::Button,
+    pub special: g::Button,
+    pub parry: g::Button,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            attack: RightTrigger,
+            special: LeftTrigger,
+            parry: RightThumb,
+        }
+    }
+}
+
+impl Bindings {
+    ///the input a player should see for what came off the pad; None if it's a default
+    /// button that's been bound to something else
+    pub fn remap(&self, input: PadInput) -> Option<PadInput> {
+        let button = match input {
+            PadInput::Pressed(button) | PadInput::Released(button) => button,
+            PadInput::Axis(..) => return Some(input),
+        };
+        let defaults = Bindings::default();
+        let mapped = if button == self.attack {
+            defaults.attack
+        } else if button == self.special {
+            defaults.special
+        } else if button == self.parry {
+            defaults.parry
+        } else if [defaults.attack, defaults.special, defaults.parry].contains(&button) {
+            return None;
+        } else {
+            button
+        };
+        match input {
+            PadInput::Pressed(_) => Some(PadInput::Pressed(mapped)),
+            _ => Some(PadInput::Released(mapped)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Options {
+    pub volume: VolumeSettings,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub rules: MatchRules,
+    //multiplies camera shake; 0 turns it off
+    pub shake: f32,
+    pub palette: Palette,
+    //draw each player's emblem over their shape, and an outline around it
+    pub emblems: bool,
+    pub outlines: bool,
+    pub bindings: Bindings,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            volume: VolumeSettings::default(),
+            fullscreen: false,
+            vsync: true,
+            rules: MatchRules::default(),
+            shake: 1.0,
+            palette: Palette::default(),
+            emblems: false,
+            outlines: false,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+impl Options {
+    pub const PATH: &'static str = "options.ron";
+    const MAX_SHAKE: f32 = 2.0;
+    const MAX_STOCKS: u32 = 9;
+
+    ///the saved options, or the defaults if there aren't any (or they can't be read)
+    pub fn load() -> Self {
+        match fs::read_to_string(Self::PATH) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|e| {
+                warn!("couldn't read {}, using the default options: {e}", Self::PATH);
+                Options::default()
+            }),
+            Err(_) => Options::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), SaveError> {
+        save_ron(Self::PATH, self)
+    }
+}
+
+//the rows of the options menu, top to bottom
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Row {
+    Master,
+    Music,
+    Sfx,
+    Fullscreen,
+    Vsync,
+    Stocks,
+    Shake,
+    Palette,
+    Emblems,
+    Outlines,
+    Attack,
+    Special,
+    Parry,
+}
+
+const ROWS: [Row; 13] = [
+    Row::Master, Row::Music, Row::Sfx, Row::Fullscreen, Row::Vsync, Row::Stocks,
+    Row::Shake, Row::Palette, Row::Emblems, Row::Outlines, Row::Attack, Row::Special, Row::Parry,
+];
+
+pub struct OptionsMenu {
+    row: usize,
+    //waiting for a pad button to bind to this row
+    rebinding: bool,
+    text: Handle<UiNode>,
+}
+
+impl OptionsMenu {
+    const VOLUME_STEP: f32 = 0.1;
+    const SHAKE_STEP: f32 = 0.25;
+
+    pub fn new(ui: &mut UserInterface, options: &Options) -> Self {
+        let text = create_text_with_background(ui, "", 300.0, 100.0, Color::opaque(255, 255, 255));
+        set_text_visibility(ui, text, true);
+        let menu = Self{row: 0, rebinding: false, text};
+        menu.refresh(ui, options);
+        menu
+    }
+
+    pub fn remove(self, ui: &mut UserInterface) {
+        //the text sits in a background border; removing that removes both
+        let border = ui.node(self.text).parent();
+        ui.send_message(WidgetMessage::remove(border, MessageDirection::ToWidget));
+    }
+
+    ///handles a key press; returns true if the options changed
+    pub fn on_key(&mut self, key: KeyCode, options: &mut Options, ui: &mut UserInterface) -> bool {
+        let before = options.clone();
+        match key {
+            KeyCode::ArrowUp => self.row = (self.row + ROWS.len() - 1) % ROWS.len(),
+            KeyCode::ArrowDown => self.row = (self.row + 1) % ROWS.len(),
+            KeyCode::ArrowLeft => Self::change(ROWS[self.row], options, -1),
+            KeyCode::ArrowRight => Self::change(ROWS[self.row], options, 1),
+            KeyCode::Enter => self.rebinding = matches!(ROWS[self.row], Row::Attack | Row::Special | Row::Parry),
+            _ => (),
+        }
+        self.refresh(ui, options);
+        before.volume != options.volume || before.fullscreen != options.fullscreen || before.vsync != options.vsync
+            || before.rules.stocks != options.rules.stocks || before.shake != options.shake
+            || before.palette != options.palette || before.emblems != options.emblems
+            || before.outlines != options.outlines
+    }
+
+    ///handles a pad button while rebinding; returns true if it was used
+    pub fn on_button(&mut self, button: g::Button, options: &mut Options, ui: &mut UserInterface) -> bool {
+        if !self.rebinding {
+            return false;
+        }
+        match ROWS[self.row] {
+            Row::Attack => options.bindings.attack = button,
+            Row::Special => options.bindings.special = button,
+            Row::Parry => options.bindings.parry = button,
+            _ => (),
+        }
+        self.rebinding = false;
+        self.refresh(ui, options);
+        true
+    }
+
+    //moves a row's value one step up or down
+    fn change(row: Row, options: &mut Options, step: i32) {
+        let volume = |v: &mut f32| *v = (*v + step as f32 * Self::VOLUME_STEP).clamp(0.0, 1.0);
+        match row {
+            Row::Master => volume(&mut options.volume.master),
+            Row::Music => volume(&mut options.volume.music),
+            Row::Sfx => volume(&mut options.volume.sfx),
+            Row::Fullscreen => options.fullscreen = !options.fullscreen,
+            Row::Vsync => options.vsync = !options.vsync,
+            //below 1 is respawning forever
+            Row::Stocks => {
+                let stocks = options.rules.stocks.unwrap_or(0) as i32 + step;
+                options.rules.stocks = match stocks.clamp(0, Options::MAX_STOCKS as i32) {
+                    0 => None,
+                    n => Some(n as u32),
+                };
+            },
+            Row::Shake => options.shake = (options.shake + step as f32 * Self::SHAKE_STEP).clamp(0.0, Options::MAX_SHAKE),
+            Row::Palette => options.palette = if step > 0 {options.palette.next()} else {options.palette.previous()},
+            Row::Emblems => options.emblems = !options.emblems,
+            Row::Outlines => options.outlines = !options.outlines,
+            Row::Attack | Row::Special | Row::Parry => (),
+        }
+    }
+
+    fn refresh(&self, ui: &mut UserInterface, options: &Options) {
+        let percent = |v: f32| format!("{:.0}%", v * 100.0);
+        let on = |b: bool| if b {"on"} else {"off"};
+        let mut text = "OPTIONS (F4 to close)\n".to_string();
+        for (i, row) in ROWS.iter().enumerate() {
+            let value = match row {
+                Row::Master => percent(options.volume.master),
+                Row::Music => percent(options.volume.music),
+                Row::Sfx => percent(options.volume.sfx),
+                Row::Fullscreen => on(options.fullscreen).to_string(),
+                Row::Vsync => format!("{} (after restart)", on(options.vsync)),
+                Row::Stocks => options.rules.stocks.map_or("off".to_string(), |s| s.to_string()),
+                Row::Shake => percent(options.shake),
+                Row::Palette => format!("{:?}", options.palette),
+                Row::Emblems => on(options.emblems).to_string(),
+                Row::Outlines => on(options.outlines).to_string(),
+                Row::Attack => format!("{:?}", options.bindings.attack),
+                Row::Special => format!("{:?}", options.bindings.special),
+                Row::Parry => format!("{:?}", options.bindings.parry),
+            };
+            let value = if i == self.row && self.rebinding {"press a button...".to_string()} else {value};
+            let cursor = if i == self.row {">"} else {" "};
+            text += &format!("\n{cursor} {row:?}: {value}");
+        }
+        ui.send_message(TextMessage::text(self.text, MessageDirection::ToWidget, text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gilrs::Button::{East, South};
+
+    #[test]
+    fn default_bindings_pass_everything_through() {
+        let bindings = Bindings::default();
+        for button in [RightTrigger, LeftTrigger, RightThumb, South] {
+            assert_eq!(bindings.remap(PadInput::Pressed(button)), Some(PadInput::Pressed(button)));
+            assert_eq!(bindings.remap(PadInput::Released(button)), Some(PadInput::Released(button)));
+        }
+    }
+
+    #[test]
+    fn bound_buttons_become_the_default_ones() {
+        let bindings = Bindings{attack: South, special: East, parry: RightThumb};
+        assert_eq!(bindings.remap(PadInput::Pressed(South)), Some(PadInput::Pressed(RightTrigger)));
+        assert_eq!(bindings.remap(PadInput::Released(South)), Some(PadInput::Released(RightTrigger)));
+        assert_eq!(bindings.remap(PadInput::Pressed(East)), Some(PadInput::Pressed(LeftTrigger)));
+        assert_eq!(bindings.remap(PadInput::Pressed(RightThumb)), Some(PadInput::Pressed(RightThumb)));
+    }
+
+    #[test]
+    fn rebound_default_buttons_are_dropped() {
+        let bindings = Bindings{attack: South, special: East, parry: RightThumb};
+        //the triggers aren't bound to anything any more, so they can't sneak through as themselves
+        assert_eq!(bindings.remap(PadInput::Pressed(RightTrigger)), None);
+        assert_eq!(bindings.remap(PadInput::Pressed(LeftTrigger)), None);
+    }
+
+    #[test]
+    fn swapped_buttons_swap() {
+        let bindings = Bindings{attack: LeftTrigger, special: RightTrigger, parry: RightThumb};
+        assert_eq!(bindings.remap(PadInput::Pressed(LeftTrigger)), Some(PadInput::Pressed(RightTrigger)));
+        assert_eq!(bindings.remap(PadInput::Pressed(RightTrigger)), Some(PadInput::Pressed(LeftTrigger)));
+    }
+
+    #[test]
+    fn sticks_are_never_remapped() {
+        let bindings = Bindings{attack: South, special: East, parry: RightThumb};
+        let stick = PadInput::Axis(g::Axis::LeftStickX, 0.5);
+        assert_eq!(bindings.remap(stick), Some(stick));
+    }
+}
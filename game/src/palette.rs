@@ -0,0 +1,192 @@
+//this module contains how players are told apart, for everyone including colour-blind players:
+// the palettes of team colours (picked in the options; see options.rs). every palette's four
+//  colours are used for the sprite, facing chevron, hud and death burst alike
+// an emblem for each player number drawn over their shape, and a dark outline around it;
+//  both can be turned on in the options so players don't have to rely on colour at all
+// PlayerTags: "P1" to "P4" next to each player's shape, always on
+use crate::*;
+use fyrox::scene::{camera::Camera, dim2::rectangle::RectangleBuilder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl Palette {
+    pub fn next(&self) -> Self {
+        match self {
+            Palette::Standard => Palette::Deuteranopia,
+            Palette::Deuteranopia => Palette::Protanopia,
+            Palette::Protanopia => Palette::Tritanopia,
+            Palette::Tritanopia => Palette::Standard,
+        }
+    }
+
+    pub fn previous(&self) -> Self {
+        self.next().next().next()
+    }
+
+    ///the team colours for slots 0 to 3
+    pub fn colors(&self) -> [Color; 4] {
+        match self {
+            //green, blue, purple, pink
+            Palette::Standard => [
+                Color::opaque(66, 245, 158),
+                Color::opaque(66, 167, 245),
+                Color::opaque(194, 136, 252),
+                Color::opaque(250, 135, 215),
+            ],
+            //no red/green pairs: orange, sky blue, yellow, reddish purple
+            Palette::Deuteranopia => [
+                Color::opaque(230, 159, 0),
+                Color::opaque(86, 180, 233),
+                Color::opaque(240, 228, 66),
+                Color::opaque(204, 121, 167),
+            ],
+            //reds look dark to protanopes, so lean on brightness: amber, blue, white, violet
+            Palette::Protanopia => [
+                Color::opaque(255, 176, 0),
+                Color::opaque(100, 143, 255),
+                Color::opaque(255, 255, 255),
+                Color::opaque(120, 94, 240),
+            ],
+            //no blue/yellow pairs: red, teal, white, pink
+            Palette::Tritanopia => [
+                Color::opaque(230, 60, 60),
+                Color::opaque(0, 190, 190),
+                Color::opaque(255, 255, 255),
+                Color::opaque(255, 150, 200),
+            ],
+        }
+    }
+
+    pub fn color(&self, slot: usize) -> Color {
+        self.colors()[slot % 4]
+    }
+}
+
+//the emblem for each slot, drawn over the shape
+const EMBLEMS: [&str; 4] = [
+    "data/White_star.png",
+    "data/White_circle.png",
+    "data/White_triangle.png",
+    "data/White_square.png",
+];
+const EMBLEM_COLOR: Color = Color::opaque(30, 30, 30);
+const OUTLINE_COLOR: Color = Color::opaque(0, 0, 0);
+//sizes relative to the sprite
+const EMBLEM_SCALE: f32 = 0.45;
+const OUTLINE_SCALE: f32 = 1.25;
+
+///adds the emblem and/or outline to a player's sprite. they're children of the sprite rather
+/// than the player, so hit flashes (set_sprite_color) leave them alone
+pub fn add_markers(graph: &mut Graph, resource_manager: &ResourceManager, sprite: Handle<Node>, slot: usize, shape: &str, options: &Options) {
+    //the emblem sits just in front of the sprite and the outline just behind it
+    let mut marker = |texture: &str, scale: f32, z: f32, color: Color| {
+        let handle = RectangleBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, z))
+                    .with_local_scale(Vector3::new(scale, scale, 1.0))
+                    .build(),
+            ),
+        )
+        .with_texture(resource_manager.request::<Texture, _>(texture))
+        .with_color(color)
+        .build(graph);
+        graph.link_nodes(handle, sprite);
+    };
+    if options.emblems {
+        marker(EMBLEMS[slot % 4], EMBLEM_SCALE, -0.01, EMBLEM_COLOR);
+    }
+    if options.outlines {
+        marker(shape, OUTLINE_SCALE, 0.01, OUTLINE_COLOR);
+    }
+}
+
+///the player number tags that follow the players around
+#[derive(Default)]
+pub struct PlayerTags {
+    tags: HashMap<Handle<Node>, Handle<UiNode>>,
+}
+
+impl PlayerTags {
+    //how far up and left of the player the tag goes, in world units
+    const OFFSET: Vector3<f32> = Vector3::new(-0.5, 0.5, 0.0);
+
+    ///moves every tag to its player; called once per frame
+    pub fn update(&mut self, graph: &Graph, ui: &mut UserInterface, players: &BTreeMap<usize, Handle<Node>>) {
+        self.tags.retain(|player, tag| {
+            let keep = players.values().any(|p| p == player);
+            if !keep {
+                ui.send_message(WidgetMessage::remove(*tag, MessageDirection::ToWidget));
+            }
+            keep
+        });
+
+        let camera = graph.find(graph.get_root(), &mut |n| n.cast::<Camera>().is_some())
+            .and_then(|(_, n)| n.cast::<Camera>());
+        let screen = ui.screen_size();
+        for (slot, handle) in players {
+            let player = match graph.try_get(*handle).and_then(|n| n.try_get_script::<Player>()) {
+                Some(player) => player,
+                None => continue,
+            };
+            let tag = *self.tags.entry(*handle).or_insert_with(|| {
+                TextBuilder::new(WidgetBuilder::new().with_foreground(Brush::Solid(player.color)))
+                    .with_text(format!("P{}", slot + 1))
+                    .build(&mut ui.build_ctx())
+            });
+            ui.send_message(WidgetMessage::visibility(tag, MessageDirection::ToWidget, !player.state.is_dead()));
+            if let Some(pos) = camera.and_then(|c| c.project(graph[*handle].global_position() + Self::OFFSET, screen)) {
+                ui.send_message(WidgetMessage::desired_position(tag, MessageDirection::ToWidget, pos));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Palette; 4] = [Palette::Standard, Palette::Deuteranopia, Palette::Protanopia, Palette::Tritanopia];
+
+    #[test]
+    fn previous_undoes_next() {
+        for palette in ALL {
+            assert_eq!(palette.next().previous(), palette);
+            assert_eq!(palette.previous().next(), palette);
+        }
+        assert_eq!(Palette::Standard.previous(), Palette::Tritanopia);
+    }
+
+    #[test]
+    fn next_visits_every_palette() {
+        let mut palette = Palette::Standard;
+        let mut seen = Vec::new();
+        for _ in 0..ALL.len() {
+            seen.push(palette);
+            palette = palette.next();
+        }
+        assert_eq!(palette, Palette::Standard);
+        assert_eq!(seen, ALL.to_vec());
+    }
+
+    #[test]
+    fn every_team_gets_its_own_colour() {
+        for palette in ALL {
+            let colors = palette.colors();
+            for i in 0..colors.len() {
+                for j in i + 1..colors.len() {
+                    assert_ne!(colors[i], colors[j], "{palette:?} slots {i} and {j}");
+                }
+            }
+            assert_eq!(palette.color(5), palette.color(1));
+        }
+    }
+}
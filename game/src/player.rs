@@ -4,40 +4,10 @@
 // health
 use crate::*;
 
-use rand::Rng;
 
 use fyrox::script::ScriptMessage;
 use gilrs::Axis;
 
-#[derive(Visit, Reflect, Debug, Clone, Default)]
-pub enum PlayerState {
-    #[default]
-    Idle,
-    Charging,
-    Dead(i32),
-    Riposting,
-    //the field holds the number of frames the player is into the action
-    Attacking(i32),
-    Hit(i32),
-    Parry(i32),
-}
-
-//This makes it so that comparing player states ignores the value of the frame
-impl PartialEq for PlayerState {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (PlayerState::Idle, PlayerState::Idle)  => true,
-            (PlayerState::Charging, PlayerState::Charging) => true,
-            (PlayerState::Dead(_), PlayerState::Dead(_))  => true,
-            (PlayerState::Riposting, PlayerState::Riposting)  => true,
-            (PlayerState::Attacking(_), PlayerState::Attacking(_))  => true,
-            (PlayerState::Hit(_), PlayerState::Hit(_))  => true,
-            (PlayerState::Parry(_), PlayerState::Parry(_))  => true,
-            _ => false
-        }
-    }
-}
-
 #[derive(Visit, Reflect, Debug, Clone, Default)]
 pub struct Player{
     pub class: Class,
@@ -48,6 +18,15 @@ pub struct Player{
     pub health: u32,
     pub charges: i32,
     pub iframes: i32,
+    //team colour of the sprite, and frames left of flashing white after a hit
+    pub color: Color,
+    pub flash: i32,
+    //lives left, if the match has a stock limit
+    pub stocks: Option<u32>,
+    //most recent state transitions, newest last
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub transitions: Vec<StateTransition>,
 }
 
 impl_component_provider!(Player,);
@@ -68,13 +47,11 @@ impl ScriptTrait for Player {
         context.message_dispatcher.subscribe_to::<Message>(context.handle);
         //self.class.clone().startup(self, context);
 
-        //tell game to update health
-        if let Some(game) = context.plugins[0].cast_mut::<Game>() {
-            game.phealthchanged = true;
-        }
+        //show our starting health on the hud
+        publish(context.plugins, GameEvent::PlayerChanged{player: context.handle});
 
         //setting up the "facing chevron"
-        let chevron = create_facing_chevron(self.facing.clone(), context);
+        let chevron = create_facing_chevron(self.facing.clone(), self.color, context);
 
         context.scene.graph.link_nodes(chevron, context.handle);
     }
@@ -82,69 +59,25 @@ impl ScriptTrait for Player {
     // Called whenever there is an event from OS (mouse click, keypress, etc.)
     fn on_os_event(&mut self, _event: &Event<()>, _context: &mut ScriptContext) {}
 
-    // Called every frame at fixed rate of 60 FPS.
+    // Called every frame.
     fn on_update(&mut self, context: &mut ScriptContext) {
-        //update the various states 
-        match self.state {
-            PlayerState::Dead(frame) => {
-                if frame > 1 {
-                    self.state = PlayerState::Dead(frame-1)
-                } else {
-                    self.respawn(context)
-                }}, //respawn coundown
-            PlayerState::Attacking(frame) => {self.check_attack(frame, context)},
-            PlayerState::Hit(frame) => {self.cont_hit(frame, context)},
-
-            PlayerState::Charging => {self.class.clone().charging(self, context)},
-            PlayerState::Parry(frame) => {self.cont_parry(frame, context)},
-            PlayerState::Riposting => {self.class.clone().riposting(self, context)}
-            _ => (),
+        //run this update's simulation tick, if there is one; none while frozen
+        if sim_tick(context.plugins) {
+            self.tick(context);
         }
 
-        match self.class {
-            Class::Barbarian if self.cooldown == 8 => {
-                if let Some(rigid_body) = context.scene.graph[context.handle.clone()].cast_mut::<RigidBody>(){
-                    rigid_body.set_lin_vel(Vector2::new(0.0, 0.0));
-                }
-            }
-            _ => {},
-        };
-
-        self.cooldown += 1;
-
-        //if currently invincible, flash and reduce i-frames
-        if self.iframes > 1 {
-            let v = context.scene.graph[context.handle.clone()].global_visibility();
-            context.scene.graph[context.handle.clone()].set_visibility(!v);
-
-            self.iframes -= 1;
-        } else if self.iframes == 1 {
-            context.scene.graph[context.handle.clone()].set_visibility(true);
-            self.iframes -= 1;
-        };
-
         //make the player face towards the facing vector
         Self::update_look(self.facing.clone(), &mut context.scene.graph[context.handle.clone()]);
 
     }
 
-
-
-
-
-
     fn on_message(&mut self,
         message: &mut dyn ScriptMessagePayload,
         ctx: &mut ScriptMessageContext,
     ) {
-        if self.state == PlayerState::Dead(1){
+        if self.state.is_dead() {
             return()
         }
-        //REMOVE THIS IF NOTHING BREAKS
-        // match self.state {
-        //     PlayerState::Dead(_) => return(),
-        //     _ => {}
-        // }
 
         if let Some(message) = message.downcast_ref::<Message>(){
             match message {
@@ -152,11 +85,11 @@ impl ScriptTrait for Player {
                     match event {
                         // put the various controller events here, as well as calls to
                         //the correct class methods-- player has a class field now!
-                        AxisChanged(axis, value, _code) => self.moveplayer(axis, value, ctx),
-                        ButtonPressed(button, _) => {
+                        PadInput::Axis(axis, value) => self.moveplayer(axis, value, ctx),
+                        PadInput::Pressed(button) => {
                             match button {
-                                RightTrigger => self.start_melee_attack(ctx),
-                                LeftTrigger => self.projectiles(ctx),
+                                RightTrigger => self.class.behavior().primary_attack(self, ctx),
+                                LeftTrigger => self.class.behavior().special(self, ctx),
                                 RightThumb => self.parry(ctx),
                                 _ => (),
                             }},
@@ -173,9 +106,15 @@ impl ScriptTrait for Player {
                     self.takehit(dam.clone(), knock.clone(), send.clone(), ctx);
                 },
                 Parried{} => {
-                    //self.class.clone().parried(self, ctx)
+                    self.class.behavior().parry_response(self, ctx)
+                },
+                Killed{} => {
+                    self.class.behavior().on_kill(self, ctx)
+                },
+                Charges{i} => {
+                    self.charges += i;
+                    publish(ctx.plugins, GameEvent::PlayerChanged{player: ctx.handle});
                 },
-                Charges{i} => {self.charges += i},
                 _ => (),
             }
         }
@@ -189,6 +128,47 @@ impl ScriptTrait for Player {
 
 
 impl Player {
+    ///one tick of the simulation clock: advances the state and every timer
+    pub fn tick(&mut self, context: &mut ScriptContext) {
+        //update the various states 
+        match self.state {
+            PlayerState::Dead(frame) => {
+                if self.stocks == Some(0) {
+                    //out of stocks; stay dead for the rest of the match
+                } else if frame > 1 {
+                    self.advance(PlayerState::Dead(frame-1));
+                } else {
+                    self.respawn(context)
+                }}, //respawn coundown
+            PlayerState::Attacking(frame) => {self.check_attack(frame, context)},
+            PlayerState::Hit(frame) => {self.cont_hit(frame, context)},
+            PlayerState::Parry(frame) => {self.cont_parry(frame, context)},
+            _ => (),
+        }
+
+        self.class.behavior().tick(self, context);
+
+        self.cooldown += 1;
+
+        //if currently invincible, flash and reduce i-frames
+        if self.iframes > 1 {
+            let v = context.scene.graph[context.handle.clone()].global_visibility();
+            context.scene.graph[context.handle.clone()].set_visibility(!v);
+
+            self.iframes -= 1;
+        } else if self.iframes == 1 {
+            context.scene.graph[context.handle.clone()].set_visibility(true);
+            self.iframes -= 1;
+        };
+
+        //flash white after being hit
+        if self.flash > 0 {
+            self.flash -= 1;
+            let color = if self.flash > 0 {Color::WHITE} else {self.color};
+            set_sprite_color(&mut context.scene.graph, context.handle, color);
+        }
+    }
+
     
     pub fn moveplayer(&mut self, axis: &Axis, value: &f32, ctx: &mut ScriptMessageContext) {
         if let Some(rigid_body) = ctx.scene.graph[ctx.handle.clone()].cast_mut::<RigidBody>() {
@@ -197,31 +177,25 @@ impl Player {
             //         PlayerState::Hit(x)
             //     }
             // }
-            match (axis, &self.class, self.state.clone()) {
-                (_, _, PlayerState::Hit(_)) => {}, //cant move when hit
-                (_, _, PlayerState::Charging) => {} //cant change direction while charging
-
-                (g::Axis::LeftStickX, Class::Barbarian, _) => {rigid_body.set_lin_vel(Vector2::new(-value*Class::BARBSPD, rigid_body.lin_vel().y));},
-                (g::Axis::LeftStickX, Class::Rogue, _) => {rigid_body.set_lin_vel(Vector2::new(-value*Class::ROGSPD, rigid_body.lin_vel().y));},
-                (g::Axis::LeftStickX, Class::Wizard, _) => {rigid_body.set_lin_vel(Vector2::new(-value*Class::WIZSPD, rigid_body.lin_vel().y));},
-                (g::Axis::LeftStickX, Class::Fighter, _) => {rigid_body.set_lin_vel(Vector2::new(-value*Class::FIGSPD, rigid_body.lin_vel().y));},
-            
-                (g::Axis::LeftStickY, Class::Barbarian, _) => {rigid_body.set_lin_vel(Vector2::new(rigid_body.lin_vel().x, value*Class::BARBSPD));},
-                (g::Axis::LeftStickY, Class::Rogue, _) => {rigid_body.set_lin_vel(Vector2::new(rigid_body.lin_vel().x, value*Class::ROGSPD));},
-                (g::Axis::LeftStickY, Class::Wizard, _) => {rigid_body.set_lin_vel(Vector2::new(rigid_body.lin_vel().x, value*Class::WIZSPD));},
-                (g::Axis::LeftStickY, Class::Fighter, _) => {rigid_body.set_lin_vel(Vector2::new(rigid_body.lin_vel().x, value*Class::FIGSPD));},
+            let spd = self.class.behavior().speed();
+            match (axis, self.state.clone()) {
+                (_, PlayerState::Hit(_)) => {}, //cant move when hit
+                (_, PlayerState::Charging) => {} //cant change direction while charging
+
+                (g::Axis::LeftStickX, _) => {rigid_body.set_lin_vel(Vector2::new(-value*spd, rigid_body.lin_vel().y));},
+                (g::Axis::LeftStickY, _) => {rigid_body.set_lin_vel(Vector2::new(rigid_body.lin_vel().x, value*spd));},
 
                 //can't turn while attacking or parrying
-                (g::Axis::RightStickX, _, PlayerState::Attacking(_)) => {},
-                (g::Axis::RightStickY, _, PlayerState::Attacking(_)) => {},
-                (g::Axis::RightStickX, _, PlayerState::Parry(_)) => {},
-                (g::Axis::RightStickY, _, PlayerState::Parry(_)) => {},
+                (g::Axis::RightStickX, PlayerState::Attacking(_)) => {},
+                (g::Axis::RightStickY, PlayerState::Attacking(_)) => {},
+                (g::Axis::RightStickX, PlayerState::Parry(_)) => {},
+                (g::Axis::RightStickY, PlayerState::Parry(_)) => {},
 
-                (g::Axis::RightStickX, _, _) if (value.clone() != 0.0) => {self.facing.x = -*value;},
-                (g::Axis::RightStickY, _, _) if (value.clone() != 0.0) => {self.facing.y = *value;},
+                (g::Axis::RightStickX, _) if (value.clone() != 0.0) => {self.facing.x = -*value;},
+                (g::Axis::RightStickY, _) if (value.clone() != 0.0) => {self.facing.y = *value;},
                 _ => (),
             }
-        } else {println!("didn't get rigidbody");} 
+        } else {warn!("didn't get rigidbody");} 
     }
 
     pub fn update_look(facing: Vector3<f32>, node: &mut Node) {
@@ -230,83 +204,70 @@ impl Player {
 
     ///checks if a melee attack can be made, and if so sends a message to weapon
     pub fn start_melee_attack(&mut self, ctx: &mut ScriptMessageContext) {
-        //check if the player is in a valid state to start an attack
-        let atk = match self.state {
-            PlayerState::Idle => true,
-            PlayerState::Charging => true,
-            _ => false
-        };
-        
-        if atk {
-            self.state = PlayerState::Attacking(1);
-            ctx.message_sender.send_to_target(self.weapon,
-                Message::Attack{s: true});
-        }
+        //the state machine only lets this through if the player is in a valid state to
+        // start an attack; entering Attacking tells the weapon to come out
+        self.transition(PlayerState::Attacking(1), ctx.message_sender);
     }
 
     ///checks if an attack should continue or end, 
     /// and messages the weapon to stop the attack if it should end
     pub fn check_attack(&mut self, frame: i32, ctx: &mut ScriptContext) {
-        let (interval, lag) = match self.class {
-            Class::Barbarian => (Class::BARBINT, Class::BARBLAG),
-            Class::Rogue => (Class::ROGINT, Class::ROGLAG),
-            Class::Wizard => (Class::WIZINT, Class::WIZLAG),
-            Class::Fighter => (Class::FIGINT, Class::FIGLAG),
-        };
-        //while in the attack
-        if frame <= interval {
-            //advance the current frame
-            self.state = PlayerState::Attacking(frame+1)
-        } else if frame < interval + lag {
-            //if we're in end lag, don't touch the weapon, just advance the frame
-            self.state = PlayerState::Attacking(frame+1)
+        let frames = self.class.behavior().melee().frames;
+        //the weapon handles startup, active and recovery; we just advance the frame
+        if frame < frames.total() {
+            self.advance(PlayerState::Attacking(frame+1));
         } else {
-            //attack is over
-            self.state = PlayerState::Idle;
-            ctx.message_sender.send_to_target(self.weapon, Message::Attack{s: false});
+            //attack is over; leaving Attacking puts the weapon away
+            self.transition(PlayerState::Idle, ctx.message_sender);
         }
     }
 
     /// called when the player has been hit by an attack.
-    pub fn takehit(&mut self, dam: u32, knock: Vector3<f32>, _send: Handle<Node>, ctx: &mut ScriptMessageContext) {
+    pub fn takehit(&mut self, dam: u32, knock: Vector3<f32>, send: Handle<Node>, ctx: &mut ScriptMessageContext) {
         //if currently invincible or dead, return
-        if (self.state == PlayerState::Dead(1)) | (self.iframes > 0) {return;}
+        if self.state.is_dead() | (self.iframes > 0) {return;}
 
-        //tell game to update health
-        if let Some(game) = ctx.plugins[0].cast_mut::<Game>() {
-            game.phealthchanged = true;
-        }
+        //hit feedback is tuned by the class of whoever landed the hit
+        let attacker = attacker_of(&ctx.scene.graph, send);
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        self.flash = feedback_tuning(&ctx.scene.graph, attacker).flash_frames;
 
         //take damage, die if necessary
         if self.health <= dam {
-            self.die(ctx);
             self.health = 0;
+            publish(ctx.plugins, GameEvent::PlayerDamaged{player: ctx.handle, attacker, damage: dam, position});
+            self.die(attacker, ctx);
+            //let whoever landed the hit know they got the kill
+            if attacker.is_some() {
+                ctx.message_sender.send_to_target(attacker, Message::Killed{});
+            }
             return;
         } else {
             self.health -= dam;
-            //set status to Hit, give self iframes
-            self.state = PlayerState::Hit(0);
+            //set status to Hit, give self iframes.
+            // being knocked out of an attack or parry puts the weapon away
+            self.transition(PlayerState::Hit(0), ctx.message_sender);
             self.iframes = Class::IFRAMES;
+            publish(ctx.plugins, GameEvent::PlayerDamaged{player: ctx.handle, attacker, damage: dam, position});
         }
         //take knockback
         if let Some(rigid_body) = ctx.scene.graph[ctx.handle.clone()].cast_mut::<RigidBody>() {
             rigid_body.set_lin_vel(Vector2::new(knock.x, knock.y));
 
         }
-        //tell weapon to vanish
-        ctx.message_sender.send_to_target(self.weapon,
-            Message::Attack{s: false}
-        );
-
     }
 
-    pub fn die(&mut self, context: &mut ScriptMessageContext) {
-        self.state = PlayerState::Dead(Class::DEATHDUR); //respawn time
-        context.message_sender.send_to_target(self.weapon,
-            Message::Attack{s: false}
-        );
+    pub fn die(&mut self, killer: Handle<Node>, context: &mut ScriptMessageContext) {
+        self.transition(PlayerState::Dead(Class::DEATHDUR), context.message_sender); //respawn time
+        if let Some(stocks) = self.stocks.as_mut() {
+            *stocks = stocks.saturating_sub(1);
+        }
         //context.scene.graph[context.handle].set_enabled(false);
         context.scene.graph[context.handle].set_visibility(false);
+
+        let position = context.scene.graph[context.handle].global_position();
+        spawn_death_burst(&mut context.scene.graph, position, self.color);
+        publish(context.plugins, GameEvent::PlayerDied{player: context.handle, killer, position});
     }
 
     //called when the respawn time runs out and the player needs to respawn
@@ -315,109 +276,78 @@ impl Player {
         //context.scene.graph[context.handle].set_enabled(true);
         context.scene.graph[context.handle].set_visibility(true);
         
-        self.state = PlayerState::Idle;
+        self.transition(PlayerState::Idle, context.message_sender);
 
         //giving the player half a second of invincibility
         self.iframes = Class::IFRAMES;
 
-        self.health = match self.class {
-            Class::Barbarian => Class::BARBHEALTH,
-            Class::Rogue => Class::ROGHEALTH,
-            Class::Wizard => Class::WIZHEALTH,
-            Class::Fighter => Class::FIGHEALTH
-        };
-
-        //tell game to update health
-        if let Some(game) = context.plugins[0].cast_mut::<Game>() {
-            game.phealthchanged = true;
-        }
+        self.health = self.class.behavior().health();
 
-        //MOVE THE PLAYER SOMEWHERE: randomized between 4 spawnpoints
-        //should make these Class constants or... something. 
-
-        let mut spawnpoint = (Vec::<f32>::new());
-
-        match rand::thread_rng().gen_range(1..5) {
-            1 => spawnpoint = Vec::from([6.0, 3.0, 0.0]),
-            2 => spawnpoint = Vec::from([-6.0, 3.0, 0.0]),
-            3 => spawnpoint = Vec::from([-6.0, -3.0, 0.0]),
-            4 => spawnpoint = Vec::from([6.0, -3.0, 0.0]),
-            _ => spawnpoint = Vec::from([6.0, 3.0, 0.0])
-        };
+        //MOVE THE PLAYER SOMEWHERE: randomized between the spawnpoints.
+        // drawn from the match rng so replays respawn players in the same places
+        let spawnpoint = match_rng(context.plugins)
+            .and_then(|rng| rng.choose(&SPAWN_POINTS).cloned())
+            .unwrap_or(SPAWN_POINTS[0]);
 
         context.scene.graph[context.handle]
         .local_transform_mut()
         .set_position(Vector3::new(spawnpoint[0], spawnpoint[1], spawnpoint[2]));
+
+        let position = Vector3::new(spawnpoint[0], spawnpoint[1], spawnpoint[2]);
+        publish(context.plugins, GameEvent::PlayerRespawned{player: context.handle, position});
     }
 
     ///called every frame while the player is hit
     pub fn cont_hit(&mut self, frame: i32, context: &mut ScriptContext) {
         if frame < Class::HITDUR {
             //if player is still stunlocked
-            self.state = PlayerState::Hit(frame+1);
+            self.advance(PlayerState::Hit(frame+1));
             //otherwise, 
         } else {
             context.scene.graph[context.handle.clone()].set_visibility(true);
-            self.state = PlayerState::Idle;
+            self.transition(PlayerState::Idle, context.message_sender);
         }
     }
 
     ///called when the player starts a parry
     pub fn parry(&mut self, ctx: &mut ScriptMessageContext) {
-        //check if player can parry
-        match self.state {
-            PlayerState::Idle => (),
-            _ => {return;}
+        //players can only parry out of Idle
+        if self.state != PlayerState::Idle {
+            return;
         }
 
-        //change state to parrying
-        self.state = PlayerState::Parry(0);
-
-        //tell weapon to start parrying
-        ctx.message_sender.send_to_target(self.weapon, 
-            Message::Start_Parry{}
-        );
+        //change state to parrying; entering Parry tells the weapon to start parrying
+        self.transition(PlayerState::Parry(0), ctx.message_sender);
+        publish(ctx.plugins, GameEvent::ParryStarted{player: ctx.handle});
     }
 
     pub fn cont_parry(&mut self, frame: i32, ctx: &mut ScriptContext) {
-        if frame == 16 {
+        if frame == Class::PARRYACTIVE {
             //put blade away
             ctx.message_sender.send_to_target(self.weapon, 
                 Message::Attack{s: false}
             );
-            self.state = PlayerState::Parry(frame+1);
-        } else if frame == 28 {
-            self.state = PlayerState::Idle;
+            self.advance(PlayerState::Parry(frame+1));
+        } else if frame == Class::PARRYLEN {
+            self.transition(PlayerState::Idle, ctx.message_sender);
         }  else {
-            self.state = PlayerState::Parry(frame+1);
+            self.advance(PlayerState::Parry(frame+1));
         }
     }
 
-    pub fn projectiles(&mut self, ctx: &mut ScriptMessageContext) {
-        match self.class{
-            Class::Barbarian => {
-                //self.start_charge(script, ctx); 
-                return;
-            }
-            Class::Rogue => {
-                //self.riposte(script, ctx); 
-                return;}
-            Class::Fighter if self.charges > 0 => {self.charges -= 1;}
-            Class::Fighter => {return;},
-            Class::Wizard => {},
-        };
-        
-        
+    ///fires a projectile in the facing direction if the player is idle and off cooldown.
+    /// returns whether a projectile was actually fired
+    pub fn fire_projectile(&mut self, ctx: &mut ScriptMessageContext) -> bool {
         if self.cooldown > Class::RCOOL && self.state == PlayerState::Idle {
             //create projectile
-            let proj = create_projectile(self.facing, ctx);
-            // set its script (hopefully happening in create_projectile now)
-            // set_script(&mut ctx.scene.graph[proj.clone()], 
-            //             Projectile{facing: self.facing.clone(), hit: false, life: 120}
-            //             );
+            let projectile = create_projectile(self.facing, ctx);
+            let position = ctx.scene.graph[ctx.handle].global_position();
+            publish(ctx.plugins, GameEvent::ProjectileFired{player: ctx.handle, projectile, position});
 
-            self.cooldown = 0
+            self.cooldown = 0;
+            return true;
         }
+        false
     }
 
 }
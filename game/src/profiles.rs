@@ -0,0 +1,192 @@
+//this module contains player profiles, saved to profiles.ron next to the game. gamepad ids change
+// between sessions, so profiles are how the game remembers who's who:
+// a name, shown on the hud and the end of match banner instead of "Player N"
+// the class they last played, picked for them when they choose the profile
+// their own button bindings and team colour; both are optional (the options and palette are used
+//  otherwise) and copied from the options when the profile is made, then edited in profiles.ron
+// lifetime stats from every finished match, and a title unlocked by lifetime wins
+//and the lobby's profile picker: under each player's class buttons a button cycles that slot
+// through Guest and every profile nobody else has picked, and typing a name next to the start
+// button and pressing "New Profile" adds one.
+use crate::*;
+use fyrox::gui::text_box::TextBoxBuilder;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LifetimeStats {
+    pub matches: u32,
+    pub wins: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub damage_dealt: u32,
+    pub damage_taken: u32,
+    pub parries: u32,
+    pub projectiles: u32,
+}
+
+impl LifetimeStats {
+    fn add(&mut self, stats: &PlayerStats, won: bool) {
+        self.matches += 1;
+        self.wins += won as u32;
+        self.kills += stats.kills;
+        self.deaths += stats.deaths;
+        self.damage_dealt += stats.damage_dealt;
+        self.damage_taken += stats.damage_taken;
+        self.parries += stats.parries;
+        self.projectiles += stats.projectiles;
+    }
+}
+
+//titles and the lifetime wins that unlock them, best last
+const TITLES: [(u32, &str); 5] = [
+    (0, "Rookie"),
+    (3, "Brawler"),
+    (10, "Contender"),
+    (25, "Veteran"),
+    (50, "Champion"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub class: Option<Class>,
+    pub bindings: Option<Bindings>,
+    pub color: Option<[u8; 3]>,
+    #[serde(default)]
+    pub stats: LifetimeStats,
+}
+
+impl Profile {
+    pub fn color(&self) -> Option<Color> {
+        self.color.map(|[r, g, b]| Color::opaque(r, g, b))
+    }
+
+    ///the best title this profile has unlocked
+    pub fn title(&self) -> &'static str {
+        TITLES.iter().rev().find(|(wins, _)| self.stats.wins >= *wins).map_or("", |(_, title)| title)
+    }
+
+    ///what the hud calls this player, e.g. "Sam the Veteran"
+    pub fn display_name(&self) -> String {
+        format!("{} the {}", self.name, self.title())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+    //which profile each slot picked in the lobby, by index into profiles
+    #[serde(skip)]
+    pub chosen: BTreeMap<usize, usize>,
+}
+
+impl Profiles {
+    pub const PATH: &'static str = "profiles.ron";
+
+    ///the saved profiles, or none if there aren't any (or they can't be read)
+    pub fn load() -> Self {
+        match fs::read_to_string(Self::PATH) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|e| {
+                warn!("couldn't read {}, starting without profiles: {e}", Self::PATH);
+                Profiles::default()
+            }),
+            Err(_) => Profiles::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<(), SaveError> {
+        save_ron(Self::PATH, self)
+    }
+
+    ///the profile a slot picked, if any
+    pub fn get(&self, slot: usize) -> Option<&Profile> {
+        self.chosen.get(&slot).and_then(|i| self.profiles.get(*i))
+    }
+
+    ///what the hud and banner call a slot
+    pub fn display_name(&self, slot: usize) -> String {
+        self.get(slot).map_or(format!("Player {}", slot + 1), |p| p.display_name())
+    }
+
+    ///moves a slot on to the next profile nobody else has picked, or back to Guest after the last one
+    pub fn cycle(&mut self, slot: usize) {
+        let start = self.chosen.remove(&slot).map_or(0, |i| i + 1);
+        let taken: Vec<usize> = self.chosen.values().cloned().collect();
+        if let Some(next) = (start..self.profiles.len()).find(|i| !taken.contains(i)) {
+            self.chosen.insert(slot, next);
+        }
+    }
+
+    ///adds a profile with the given bindings; returns false if the name is empty or taken
+    pub fn add(&mut self, name: &str, bindings: Bindings) -> bool {
+        let name = name.trim();
+        if name.is_empty() || self.profiles.iter().any(|p| p.name == name) {
+            return false;
+        }
+        self.profiles.push(Profile{name: name.to_string(), class: None, bindings: Some(bindings), color: None, stats: LifetimeStats::default()});
+        true
+    }
+
+    ///adds a finished match to the profile in a slot, remembering the class they played
+    pub fn record(&mut self, slot: usize, class: Class, stats: &PlayerStats, won: bool) {
+        let profile = match self.chosen.get(&slot).and_then(|i| self.profiles.get_mut(*i)) {
+            Some(profile) => profile,
+            None => return,
+        };
+        let title = profile.title();
+        profile.stats.add(stats, won);
+        profile.class = Some(class);
+        if profile.title() != title {
+            info!("{} unlocked the title {}", profile.name, profile.title());
+        }
+    }
+}
+
+///the profile picker's widgets. the buttons themselves go in Game::menu so they're hidden with it;
+/// these are the texts on them and the name box
+pub struct ProfileLobby {
+    names: [Handle<UiNode>; 4],
+    pub name_box: Handle<UiNode>,
+    //what's been typed in the name box
+    pub typed: String,
+}
+
+impl ProfileLobby {
+    ///builds the picker, pushing its buttons onto the menu: one per slot, then "New Profile",
+    /// then the name box
+    pub fn new(ui: &mut UserInterface, menu: &mut Vec<Handle<UiNode>>) -> Self {
+        let ctx = &mut ui.build_ctx();
+        let mut names = [Handle::NONE; 4];
+        for (slot, name) in names.iter_mut().enumerate() {
+            *name = TextBuilder::new(WidgetBuilder::new())
+                .with_text(format!("P{}: Guest", slot + 1))
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .build(ctx);
+            menu.push(ButtonBuilder::new(
+                WidgetBuilder::new()
+                    .with_desired_position(Vector2::new(slot as f32 * 200.0, 70.0))
+                    .with_width(200.0)
+                    .with_height(20.0),
+            )
+            .with_content(*name)
+            .build(ctx));
+        }
+        menu.push(create_player_class_button(ui, 800.0, 150.0, 200.0, 30.0, "New Profile", Option::None, Some(HorizontalAlignment::Center)));
+        let name_box = TextBoxBuilder::new(WidgetBuilder::new()
+            .with_desired_position(Vector2::new(800.0, 120.0))
+            .with_width(200.0)
+            .with_height(25.0))
+            .build(&mut ui.build_ctx());
+        menu.push(name_box);
+        Self{names, name_box, typed: String::new()}
+    }
+
+    ///shows which profile each slot has picked
+    pub fn refresh(&self, ui: &mut UserInterface, profiles: &Profiles) {
+        for (slot, name) in self.names.iter().enumerate() {
+            let profile = profiles.get(slot).map_or("Guest".to_string(), |p| p.display_name());
+            ui.send_message(TextMessage::text(*name, MessageDirection::ToWidget, format!("P{}: {profile}", slot + 1)));
+        }
+    }
+}
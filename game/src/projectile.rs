@@ -4,6 +4,8 @@ use crate::*;
 #[derive(Visit, Reflect, Debug, Clone, Default)]
 
 pub struct Projectile {
+    //the player who fired it
+    pub owner: Handle<Node>,
     pub facing: Vector3<f32>,
     pub hit: bool,
     pub life: u32,
@@ -34,9 +36,9 @@ impl ScriptTrait for Projectile {
         if let Some(message) = message.downcast_ref::<Message>(){
             match message {
                 Message::Attack{s} if (!*s & !self.hit) => {
-                    println!("recieved!");
+                    trace!("recieved!");
                     self.hit = true;
-                    self.life = 5;
+                    self.life = Class::PROJLINGER;
                     ctx.scene.graph[ctx.handle].set_visibility(false);
                 },
                 _ => (),
@@ -44,13 +46,26 @@ impl ScriptTrait for Projectile {
         }
     }
 
-    // Called every frame at fixed rate of 60 FPS.
+    // Called every frame.
     fn on_update(&mut self, ctx: &mut ScriptContext) {
-        if self.life == 0 {
-            ctx.scene.graph.remove_node(ctx.handle);
-            return;
+        if sim_tick(ctx.plugins) {
+            if self.life == 0 {
+                ctx.scene.graph.remove_node(ctx.handle);
+                return;
+            }
+            self.tick(ctx);
         }
+    }
 
+    // Returns unique script ID for serialization needs.
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+impl Projectile {
+    ///one tick of the simulation clock: counts down the projectile's life and checks for hits
+    pub fn tick(&mut self, ctx: &mut ScriptContext) {
         self.life -= 1;
         //prevent crash in last frame after deletion.
         if self.hit {return;}
@@ -112,14 +127,9 @@ impl ScriptTrait for Projectile {
             // }
         }
         if self.hit {
-            //destroy the projectile 5 frames after hit
-            self.life = 5;
+            //destroy the projectile a few ticks after hit
+            self.life = Class::PROJLINGER;
             ctx.scene.graph[ctx.handle].set_visibility(false);
         }
     }
-
-    // Returns unique script ID for serialization needs.
-    fn id(&self) -> Uuid {
-        Self::type_uuid()
-    }
 }
@@ -0,0 +1,144 @@
+//this module contains replay recording and playback.
+//a replay is everything needed to play a match out again: the rules, every player's class, the
+// seed of the match rng, and the input each player sent on each frame. while a match is being
+// recorded, Game::send_input writes every input into the replay; while one is being played back,
+// gilrs is ignored and the recorded inputs are fed to the players instead.
+//replays are saved as RON files with a version number, so old replays can be turned away
+// instead of played back wrong.
+//playback controls: P pauses, . steps one tick while paused, F toggles fast-forward
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+//bump this whenever a change would make old replays play out differently
+pub const REPLAY_VERSION: u32 = 2;
+
+///the inputs sent before one tick of the simulation clock; ticks without any input aren't stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub frame: u32,
+    //(player slot, input)
+    pub inputs: Vec<(usize, PadInput)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub rules: MatchRules,
+    //(player slot, class)
+    pub players: Vec<(usize, Class)>,
+    pub frames: Vec<ReplayFrame>,
+    //how many ticks the match lasted
+    pub length: u32,
+}
+
+///why a replay couldn't be loaded; saving goes through save_ron like the game's other files
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Format(String),
+    //the replay was made by a different version of the game
+    Version(u32),
+}
+
+impl Replay {
+    pub const DEFAULT_PATH: &'static str = "replays/latest.ron";
+
+    pub fn new(seed: u64, rules: MatchRules, players: Vec<(usize, Class)>) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            seed,
+            rules,
+            players,
+            frames: Vec::new(),
+            length: 0,
+        }
+    }
+
+    pub fn record(&mut self, frame: u32, slot: usize, input: PadInput) {
+        match self.frames.last_mut() {
+            Some(last) if last.frame == frame => last.inputs.push((slot, input)),
+            _ => self.frames.push(ReplayFrame{frame, inputs: vec![(slot, input)]}),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), SaveError> {
+        save_ron(path, self)
+    }
+
+    pub fn load(path: &str) -> Result<Self, ReplayError> {
+        let text = fs::read_to_string(path).map_err(ReplayError::Io)?;
+        let replay: Replay = ron::from_str(&text).map_err(|e| ReplayError::Format(e.to_string()))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::Version(replay.version));
+        }
+        Ok(replay)
+    }
+}
+
+///a replay being played back. Game::update decides whether a tick runs each update (none while
+/// paused, one when stepping) and asks for the inputs up to it. fast-forward doesn't run more ticks
+/// per update; it gets more updates out of the executor, so physics keeps stepping with every tick
+pub struct Playback {
+    pub replay: Replay,
+    //index into replay.frames of the next frame with input
+    cursor: usize,
+    pub paused: bool,
+    //play one tick, then pause again
+    pub step: bool,
+    //how many times faster than recorded it plays
+    pub speed: u32,
+    //extra engine updates still owed from fast-forwarding
+    catching_up: u32,
+}
+
+impl Playback {
+    pub const FAST_FORWARD: u32 = 4;
+
+    pub fn new(replay: Replay) -> Self {
+        Self {replay, cursor: 0, paused: false, step: false, speed: 1, catching_up: 0}
+    }
+
+    pub fn finished(&self, tick: u32) -> bool {
+        tick >= self.replay.length
+    }
+
+    ///every recorded input from before the given tick that hasn't been handed out yet
+    pub fn inputs_until(&mut self, tick: u32) -> Vec<(usize, PadInput)> {
+        let mut inputs = Vec::new();
+        while let Some(recorded) = self.replay.frames.get(self.cursor) {
+            if recorded.frame >= tick {
+                break;
+            }
+            inputs.extend(recorded.inputs.iter().cloned());
+            self.cursor += 1;
+        }
+        inputs
+    }
+
+    pub fn toggle_fast_forward(&mut self) {
+        self.speed = if self.speed == 1 {Self::FAST_FORWARD} else {1};
+    }
+
+    ///called every engine update: while fast-forwarding, every update the executor runs on its own
+    /// adds `speed - 1` more to its lag, the same way rollback catches up
+    pub fn fast_forward(&mut self, lag: &mut f32, dt: f32) {
+        if self.catching_up > 0 {
+            self.catching_up -= 1;
+        } else if self.speed > 1 && !self.paused {
+            self.catching_up = self.speed - 1;
+            *lag += dt * self.catching_up as f32;
+        }
+    }
+}
+
+///what the replay system is doing this match
+#[derive(Default)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    Recording(Replay),
+    Playback(Playback),
+}
+
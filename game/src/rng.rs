@@ -0,0 +1,59 @@
+//this module contains MatchRng, the random number generator for everything random in gameplay
+// (respawn points, and later things like crits or pickup spawns). it's owned by the Game plugin
+// and seeded when a match starts, so a match with the same seed and the same inputs plays out
+// the same way every time; that's what replays and netplay rely on.
+//scripts get at it with match_rng(). anything purely cosmetic (camera shake, particles)
+// doesn't go through here, so it can't knock gameplay out of sync.
+use crate::*;
+use rand::{distributions::uniform::{SampleRange, SampleUniform}, rngs::StdRng, Rng, SeedableRng};
+
+#[derive(Clone)]
+pub struct MatchRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl MatchRng {
+    pub fn new(seed: u64) -> Self {
+        Self {seed, rng: StdRng::seed_from_u64(seed)}
+    }
+
+    ///a MatchRng with a seed nobody picked
+    pub fn fresh() -> Self {
+        Self::new(rand::thread_rng().gen())
+    }
+
+    ///the seed this rng started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn gen_range<T: SampleUniform, R: SampleRange<T>>(&mut self, range: R) -> T {
+        self.rng.gen_range(range)
+    }
+
+    ///true with the given probability (0.0 to 1.0)
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.rng.gen_bool(probability.clamp(0.0, 1.0))
+    }
+
+    ///a random element of a slice
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.rng.gen_range(0..items.len())])
+        }
+    }
+}
+
+impl Default for MatchRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+///the match rng, for scripts
+pub fn match_rng(plugins: &mut [Box<dyn Plugin>]) -> Option<&mut MatchRng> {
+    game_mut(plugins).map(|game| &mut game.rng)
+}
@@ -0,0 +1,34 @@
+//the Rogue: fast and fragile, with a quick weak swing
+use crate::*;
+use crate::behavior::{ClassBehavior, MeleeStats};
+use fyrox::scene::dim2::collider::CuboidShape;
+
+pub struct RogueBehavior;
+
+impl ClassBehavior for RogueBehavior {
+    fn health(&self) -> u32 { Class::ROGHEALTH }
+    fn speed(&self) -> f32 { Class::ROGSPD }
+    fn sprite(&self) -> &'static str { "data/White_triangle.png" }
+    fn weapon_shape(&self) -> CuboidShape { Class::ROGWEP }
+
+    fn melee(&self) -> MeleeStats {
+        MeleeStats {
+            frames: Class::ROGFRAMES,
+            swing_speed: Class::ROGWEPSPD,
+            damage: Class::ROGDAM,
+            knockback: Class::ROGKNOCK,
+        }
+    }
+
+    fn sounds(&self) -> SoundSet {
+        SoundSet {
+            swing: "data/sounds/rogue_swing.ogg",
+            ..SoundSet::default()
+        }
+    }
+
+    ///the rogue has no special yet, so the left trigger does nothing, same as before the
+    /// ClassBehavior split. the old riposte code in class.rs was never hooked up to a button;
+    /// it can come back here (with the Riposting state) once it's been balanced
+    fn special(&self, _player: &mut Player, _ctx: &mut ScriptMessageContext) {}
+}
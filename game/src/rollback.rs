@@ -0,0 +1,716 @@
+//this module contains rollback netcode for two-player peer-to-peer matches, GGPO style.
+//both peers run the whole simulation. every tick:
+// we save the combat state (see CombatState) before the tick runs
+// our own input for the tick is sent to the peer (a few ticks early, see INPUT_DELAY)
+// if the peer's input for the tick hasn't arrived yet we guess that they didn't do anything new,
+//  since PadInputs are changes (an axis moving, a button going down) rather than whole pad states
+//when the peer's real input turns up and it wasn't what we guessed, we put the saved state from
+// that tick back and simulate up to the present again with the real input. that happens inside
+// one rendered frame: Game hands the executor extra lag, so it runs extra engine updates (scripts
+// and physics) before drawing anything.
+//the transport is pluggable: UDP between machines, wrapped in DelayedTransport to fake latency
+// and packet loss on one machine, or an in-memory LoopbackTransport pair within one process.
+//configured with environment variables:
+// SHAPEFIGHTS_PEER=<address of the other peer>
+// SHAPEFIGHTS_BIND=<address to listen on> (default 0.0.0.0:7778)
+// SHAPEFIGHTS_LATENCY_MS / SHAPEFIGHTS_LOSS=<0.0 to 1.0> to simulate a bad connection
+//e.g. on one machine:
+// SHAPEFIGHTS_BIND=127.0.0.1:7778 SHAPEFIGHTS_PEER=127.0.0.1:7779 SHAPEFIGHTS_LATENCY_MS=80 cargo run --package executor
+// SHAPEFIGHTS_BIND=127.0.0.1:7779 SHAPEFIGHTS_PEER=127.0.0.1:7778 SHAPEFIGHTS_LATENCY_MS=80 cargo run --package executor
+//physics engine internals (contact caches and the like) aren't saved, only what the scripts and
+// bodies hold, so a resimulation can come out very slightly differently from the first run.
+use crate::*;
+use fyrox::scene::transform::Transform;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::{SocketAddr, UdpSocket},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+//ticks between reading a local input and it taking effect; hides a little latency for free
+pub const INPUT_DELAY: u32 = 2;
+//furthest we'll run ahead of the peer's confirmed input before waiting for them
+pub const MAX_ROLLBACK: u32 = 8;
+const MAX_PACKET: usize = 4096;
+//how often to say hello (and resend Start) until the match is running
+const HANDSHAKE_RETRY: Duration = Duration::from_millis(250);
+
+///something that can carry packets to the other peer
+pub trait Transport {
+    fn send(&mut self, bytes: Vec<u8>);
+    ///the next packet that has arrived, if any
+    fn recv(&mut self) -> Option<Vec<u8>>;
+}
+
+pub struct UdpTransport {
+    socket: UdpSocket,
+    peer: SocketAddr,
+}
+
+impl UdpTransport {
+    pub fn new(bind: SocketAddr, peer: SocketAddr) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(bind)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {socket, peer})
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, bytes: Vec<u8>) {
+        if let Err(e) = self.socket.send_to(&bytes, self.peer) {
+            //the peer not being up yet is normal during the handshake
+            if e.kind() != ErrorKind::ConnectionRefused {
+                warn!("couldn't send to peer: {e}");
+            }
+        }
+    }
+
+    fn recv(&mut self) -> Option<Vec<u8>> {
+        let mut buf = [0u8; MAX_PACKET];
+        loop {
+            match self.socket.recv_from(&mut buf) {
+                Ok((len, addr)) if addr == self.peer => return Some(buf[..len].to_vec()),
+                Ok(_) => continue,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return None,
+                Err(e) if e.kind() == ErrorKind::ConnectionReset || e.kind() == ErrorKind::ConnectionRefused => continue,
+                Err(e) => {
+                    error!("network error: {e}");
+                    return None;
+                },
+            }
+        }
+    }
+}
+
+//packets on their way through a LoopbackTransport
+type Queue = Arc<Mutex<VecDeque<Vec<u8>>>>;
+
+///an in-memory transport; LoopbackTransport::pair gives two ends that talk to each other
+pub struct LoopbackTransport {
+    outgoing: Queue,
+    incoming: Queue,
+}
+
+impl LoopbackTransport {
+    pub fn pair() -> (Self, Self) {
+        let a: Queue = Default::default();
+        let b: Queue = Default::default();
+        (Self {outgoing: a.clone(), incoming: b.clone()}, Self {outgoing: b, incoming: a})
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&mut self, bytes: Vec<u8>) {
+        self.outgoing.lock().unwrap().push_back(bytes);
+    }
+
+    fn recv(&mut self) -> Option<Vec<u8>> {
+        self.incoming.lock().unwrap().pop_front()
+    }
+}
+
+///wraps another transport, holding every outgoing packet back for `latency` (plus up to
+/// `jitter`) and dropping a fraction `loss` of them, to test against a bad connection
+pub struct DelayedTransport<T: Transport> {
+    inner: T,
+    latency: Duration,
+    jitter: Duration,
+    loss: f64,
+    held: VecDeque<(Instant, Vec<u8>)>,
+}
+
+impl<T: Transport> DelayedTransport<T> {
+    pub fn new(inner: T, latency: Duration, jitter: Duration, loss: f64) -> Self {
+        Self {inner, latency, jitter, loss, held: VecDeque::new()}
+    }
+
+    fn flush(&mut self) {
+        while let Some((due, _)) = self.held.front() {
+            if *due > Instant::now() {
+                break;
+            }
+            let (_, bytes) = self.held.pop_front().unwrap();
+            self.inner.send(bytes);
+        }
+    }
+}
+
+impl<T: Transport> Transport for DelayedTransport<T> {
+    fn send(&mut self, bytes: Vec<u8>) {
+        //cosmetic randomness, not the match rng; this is the "network", not the game
+        let mut rng = rand::thread_rng();
+        if rng.gen_bool(self.loss.clamp(0.0, 1.0)) {
+            return;
+        }
+        let jitter = self.jitter.mul_f64(rng.gen_range(0.0..1.0));
+        let due = Instant::now() + self.latency + jitter;
+        //keep the queue in arrival order
+        let at = self.held.iter().position(|(d, _)| *d > due).unwrap_or(self.held.len());
+        self.held.insert(at, (due, bytes));
+        self.flush();
+    }
+
+    fn recv(&mut self) -> Option<Vec<u8>> {
+        self.flush();
+        self.inner.recv()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum PeerMsg {
+    //sent until the match starts; the peer with the lower nonce is player 1 and picks the seed
+    Hello{ nonce: u64, class: Class },
+    Start{ seed: u64, rules: MatchRules, players: Vec<(usize, Class)> },
+    //our inputs for ticks start, start+1, ...; ack is the first tick of theirs we're missing
+    Inputs{ start: u32, ticks: Vec<Vec<PadInput>>, ack: u32 },
+}
+
+///a rigid body's place in the world
+#[derive(Debug, Clone)]
+pub struct BodyState {
+    pub handle: Handle<Node>,
+    pub position: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub velocity: Vector2<f32>,
+    pub visible: bool,
+}
+
+impl BodyState {
+    pub fn capture(graph: &Graph, handle: Handle<Node>) -> Option<Self> {
+        let node = graph.try_get(handle)?;
+        Some(Self {
+            handle,
+            position: **node.local_transform().position(),
+            rotation: **node.local_transform().rotation(),
+            velocity: node.cast::<RigidBody>().map_or(Vector2::new(0.0, 0.0), |b| b.lin_vel()),
+            visible: node.visibility(),
+        })
+    }
+
+    pub fn restore(&self, graph: &mut Graph) {
+        if let Some(node) = graph.try_get_mut(self.handle) {
+            node.local_transform_mut().set_position(self.position).set_rotation(self.rotation);
+            node.set_visibility(self.visible);
+            if let Some(body) = node.cast_mut::<RigidBody>() {
+                body.set_lin_vel(self.velocity);
+            }
+        }
+    }
+
+    fn transform(&self) -> Transform {
+        TransformBuilder::new()
+            .with_local_position(self.position)
+            .with_local_rotation(self.rotation)
+            .build()
+    }
+}
+
+///everything about the match that a tick can change
+pub struct CombatState {
+    pub tick: u32,
+    pub players: Vec<(BodyState, Player)>,
+    //(body, script, whether its collider is a sensor)
+    pub weapons: Vec<(BodyState, Weapon, bool)>,
+    pub projectiles: Vec<(BodyState, Projectile)>,
+    pub rng: MatchRng,
+    pub stats: MatchStats,
+    pub hitstop: i32,
+}
+
+impl CombatState {
+    pub fn capture(tick: u32, game: &Game, graph: &Graph) -> Self {
+        let mut state = Self {
+            tick,
+            players: Vec::new(),
+            weapons: Vec::new(),
+            projectiles: Vec::new(),
+            rng: game.rng.clone(),
+            stats: game.stats.clone(),
+            hitstop: game.feedback.hitstop,
+        };
+        for handle in game.players.values() {
+            let player = match graph.try_get(*handle).and_then(|n| n.try_get_script::<Player>()) {
+                Some(player) => player.clone(),
+                None => continue,
+            };
+            if let (Some(body), Some(weapon)) = (BodyState::capture(graph, player.weapon),
+                graph.try_get(player.weapon).and_then(|n| n.try_get_script::<Weapon>()))
+            {
+                let sensor = graph.find(player.weapon, &mut |c| c.is_collider2d())
+                    .map_or(true, |(_, c)| c.as_collider2d().is_sensor());
+                state.weapons.push((body, weapon.clone(), sensor));
+            }
+            if let Some(body) = BodyState::capture(graph, *handle) {
+                state.players.push((body, player));
+            }
+        }
+        for (handle, node) in graph.pair_iter() {
+            if let Some(projectile) = node.try_get_script::<Projectile>() {
+                if let Some(body) = BodyState::capture(graph, handle) {
+                    state.projectiles.push((body, projectile.clone()));
+                }
+            }
+        }
+        state
+    }
+
+    ///puts the match back the way it was when this state was captured
+    pub fn restore(&self, game: &mut Game, graph: &mut Graph, resource_manager: &ResourceManager) {
+        for (body, player) in &self.players {
+            body.restore(graph);
+            let color = if player.flash > 0 {Color::WHITE} else {player.color};
+            set_sprite_color(graph, body.handle, color);
+            if let Some(script) = graph.try_get_mut(body.handle).and_then(|n| n.try_get_script_mut::<Player>()) {
+                *script = player.clone();
+            }
+        }
+        for (body, weapon, sensor) in &self.weapons {
+            body.restore(graph);
+            if let Some((collider, _)) = graph.find(body.handle, &mut |c| c.is_collider2d()) {
+                graph[collider].as_collider2d_mut().set_is_sensor(*sensor);
+            }
+            if let Some(script) = graph.try_get_mut(body.handle).and_then(|n| n.try_get_script_mut::<Weapon>()) {
+                *script = weapon.clone();
+            }
+        }
+
+        //projectiles come and go, so rather than patch them up, replace them all
+        let current: Vec<Handle<Node>> = graph.pair_iter()
+            .filter(|(_, n)| n.try_get_script::<Projectile>().is_some())
+            .map(|(h, _)| h)
+            .collect();
+        for handle in current {
+            graph.remove_node(handle);
+        }
+        for (body, projectile) in &self.projectiles {
+            let handle = spawn_projectile(graph, resource_manager, body.transform(), body.velocity, projectile.clone());
+            graph[handle].set_visibility(body.visible);
+        }
+
+        game.rng = self.rng.clone();
+        game.stats = self.stats.clone();
+        game.feedback.hitstop = self.hitstop;
+    }
+}
+
+enum Phase {
+    //saying hello until we've heard from the peer and the match starts
+    Handshake {
+        nonce: u64,
+        //our class, once we've pressed start
+        class: Option<Class>,
+        //the peer's nonce and class, once we've heard from them
+        peer: Option<(u64, Class)>,
+        last_sent: Instant,
+        //player 1 keeps resending Start until the peer's inputs show up
+        start: Option<PeerMsg>,
+    },
+    Running,
+}
+
+///the match start the peers agreed on
+pub struct PeerStart {
+    pub seed: u64,
+    pub rules: MatchRules,
+    pub players: Vec<(usize, Class)>,
+}
+
+pub struct RollbackSession {
+    transport: Box<dyn Transport>,
+    phase: Phase,
+    pub local_slot: usize,
+    pub remote_slot: usize,
+    //our inputs by the tick they take effect on
+    local_inputs: BTreeMap<u32, Vec<PadInput>>,
+    //inputs read since the last live tick, waiting to be given a tick
+    pending_local: Vec<PadInput>,
+    //the peer's real inputs, by tick
+    remote_inputs: BTreeMap<u32, Vec<PadInput>>,
+    //the first tick we don't have the peer's input for
+    remote_confirmed: u32,
+    //the first tick of ours the peer doesn't have yet
+    remote_ack: u32,
+    //the earliest tick that turned out to be mispredicted
+    rollback_to: Option<u32>,
+    //the newest tick we've simulated, and where a resimulation is catching up to
+    live_tick: u32,
+    pub resim_target: Option<u32>,
+    snapshots: VecDeque<CombatState>,
+    //player 1's Start, resent whenever the peer says hello until their inputs arrive
+    pending_start: Option<PeerMsg>,
+}
+
+impl RollbackSession {
+    pub fn new(transport: Box<dyn Transport>) -> Self {
+        Self {
+            transport,
+            phase: Phase::Handshake {
+                nonce: rand::thread_rng().gen(),
+                class: None,
+                peer: None,
+                last_sent: Instant::now() - HANDSHAKE_RETRY,
+                start: None,
+            },
+            local_slot: 0,
+            remote_slot: 1,
+            local_inputs: BTreeMap::new(),
+            pending_local: Vec::new(),
+            remote_inputs: BTreeMap::new(),
+            remote_confirmed: 0,
+            remote_ack: 0,
+            rollback_to: None,
+            live_tick: 0,
+            resim_target: None,
+            snapshots: VecDeque::new(),
+            pending_start: None,
+        }
+    }
+
+    ///a session set up from SHAPEFIGHTS_PEER and friends, if SHAPEFIGHTS_PEER is set
+    pub fn from_env() -> Option<Self> {
+        let peer: SocketAddr = match std::env::var("SHAPEFIGHTS_PEER").ok()?.parse() {
+            Ok(addr) => addr,
+            Err(e) => {
+                warn!("bad address in SHAPEFIGHTS_PEER: {e}");
+                return None;
+            },
+        };
+        let bind: SocketAddr = std::env::var("SHAPEFIGHTS_BIND").ok()
+            .and_then(|a| a.parse().ok())
+            .unwrap_or_else(|| "0.0.0.0:7778".parse().unwrap());
+        let udp = match UdpTransport::new(bind, peer) {
+            Ok(udp) => udp,
+            Err(e) => {
+                warn!("couldn't open rollback socket, playing offline: {e}");
+                return None;
+            },
+        };
+        let latency = std::env::var("SHAPEFIGHTS_LATENCY_MS").ok().and_then(|ms| ms.parse().ok()).unwrap_or(0);
+        let loss = std::env::var("SHAPEFIGHTS_LOSS").ok().and_then(|l| l.parse().ok()).unwrap_or(0.0);
+        let transport: Box<dyn Transport> = if latency > 0 || loss > 0.0 {
+            let latency = Duration::from_millis(latency);
+            Box::new(DelayedTransport::new(udp, latency, latency / 4, loss))
+        } else {
+            Box::new(udp)
+        };
+        Some(Self::new(transport))
+    }
+
+    fn send(&mut self, msg: &PeerMsg) {
+        match bincode::serialize(msg) {
+            Ok(bytes) => self.transport.send(bytes),
+            Err(e) => error!("couldn't encode rollback message: {e}"),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.phase, Phase::Running)
+    }
+
+    pub fn resimulating(&self) -> bool {
+        self.resim_target.is_some()
+    }
+
+    ///we pressed start, playing as `class`. player 1 starts the match as soon as both
+    /// peers are ready; returns the start if that's now
+    pub fn ready(&mut self, class: Class, rules: &MatchRules) -> Option<PeerStart> {
+        if let Phase::Handshake{class: ours, ..} = &mut self.phase {
+            *ours = Some(class);
+        }
+        self.try_start(rules)
+    }
+
+    //player 1: start the match if we and the peer are both ready
+    fn try_start(&mut self, rules: &MatchRules) -> Option<PeerStart> {
+        let (nonce, class, peer, start) = match &mut self.phase {
+            Phase::Handshake{nonce, class: Some(class), peer: Some(peer), start} => (*nonce, class.clone(), peer.clone(), start),
+            _ => return None,
+        };
+        if nonce > peer.0 || start.is_some() {
+            return None;
+        }
+        let seed = rand::thread_rng().gen();
+        let players = vec![(0, class), (1, peer.1)];
+        let msg = PeerMsg::Start{seed, rules: rules.clone(), players: players.clone()};
+        *start = Some(msg.clone());
+        self.send(&msg);
+        self.begin(0);
+        Some(PeerStart{seed, rules: rules.clone(), players})
+    }
+
+    fn begin(&mut self, local_slot: usize) {
+        self.local_slot = local_slot;
+        self.remote_slot = 1 - local_slot;
+        info!("rollback match starting as player {}", local_slot + 1);
+        //player 1 keeps its Start around to resend; see poll
+        if let Phase::Handshake{start, ..} = &mut self.phase {
+            self.pending_start = start.take();
+        }
+        self.phase = Phase::Running;
+    }
+
+    ///a local input; it'll take effect INPUT_DELAY ticks after the next live tick
+    pub fn add_local_input(&mut self, input: PadInput) {
+        if self.is_running() {
+            self.pending_local.push(input);
+        }
+    }
+
+    ///reads everything the peer sent. returns the match start if the peer just started it
+    pub fn poll(&mut self, rules: &MatchRules) -> Option<PeerStart> {
+        let mut started = None;
+
+        let mut hello = None;
+        if let Phase::Handshake{nonce, class: Some(class), last_sent, ..} = &mut self.phase {
+            if last_sent.elapsed() >= HANDSHAKE_RETRY {
+                *last_sent = Instant::now();
+                hello = Some(PeerMsg::Hello{nonce: *nonce, class: class.clone()});
+            }
+        }
+        if let Some(hello) = hello {
+            self.send(&hello);
+        }
+
+        while let Some(bytes) = self.transport.recv() {
+            let msg: PeerMsg = match bincode::deserialize(&bytes) {
+                Ok(msg) => msg,
+                Err(_) => continue,
+            };
+            match msg {
+                PeerMsg::Hello{nonce, class} => {
+                    if let Phase::Handshake{peer, ..} = &mut self.phase {
+                        *peer = Some((nonce, class));
+                    } else if let Some(start) = self.pending_start.clone() {
+                        //they're still waiting for our Start
+                        self.send(&start);
+                    }
+                },
+                PeerMsg::Start{seed, rules, players} => {
+                    if !self.is_running() {
+                        self.begin(1);
+                        started = Some(PeerStart{seed, rules, players});
+                    }
+                },
+                PeerMsg::Inputs{start, ticks, ack} => {
+                    self.pending_start = None;
+                    self.remote_ack = self.remote_ack.max(ack);
+                    for (i, inputs) in ticks.into_iter().enumerate() {
+                        let tick = start + i as u32;
+                        if tick < self.remote_confirmed {
+                            continue;
+                        }
+                        //we already ran this tick guessing they did nothing; were we wrong?
+                        if tick < self.live_tick && !inputs.is_empty() {
+                            self.rollback_to = Some(self.rollback_to.map_or(tick, |r| r.min(tick)));
+                        }
+                        self.remote_inputs.insert(tick, inputs);
+                    }
+                    while self.remote_inputs.contains_key(&self.remote_confirmed) {
+                        self.remote_confirmed += 1;
+                    }
+                },
+            }
+        }
+
+        if started.is_none() {
+            started = self.try_start(rules);
+        }
+        started
+    }
+
+    ///false if we're too far ahead of the peer and should wait for them
+    pub fn can_advance(&self, tick: u32) -> bool {
+        self.resimulating() || tick < self.remote_confirmed + MAX_ROLLBACK
+    }
+
+    ///the state to roll back to, if a misprediction turned up and we still have it.
+    /// the states after it are thrown away; resimulating saves them again
+    pub fn take_rollback(&mut self) -> Option<CombatState> {
+        let tick = self.rollback_to.take()?;
+        self.snapshots.retain(|s| s.tick <= tick);
+        match self.snapshots.pop_back() {
+            Some(state) if state.tick == tick => Some(state),
+            _ => {
+                warn!("mispredicted tick {tick} is too old to roll back to");
+                None
+            },
+        }
+    }
+
+    ///called before running tick `tick`: saves the state and returns every input for it
+    pub fn begin_tick(&mut self, tick: u32, state: CombatState) -> Vec<(usize, PadInput)> {
+        self.snapshots.push_back(state);
+        //we can never roll back past the peer's confirmed input
+        while self.snapshots.front().map_or(false, |s| s.tick < self.remote_confirmed) {
+            self.snapshots.pop_front();
+        }
+
+        if !self.resimulating() {
+            self.live_tick = tick + 1;
+            //give the inputs read since last tick their tick, and tell the peer
+            let inputs = std::mem::take(&mut self.pending_local);
+            self.local_inputs.entry(tick + INPUT_DELAY).or_default().extend(inputs);
+            for t in tick..=tick + INPUT_DELAY {
+                self.local_inputs.entry(t).or_default();
+            }
+            self.send_inputs(tick + INPUT_DELAY);
+        }
+
+        let mut inputs: Vec<(usize, PadInput)> = Vec::new();
+        if let Some(local) = self.local_inputs.get(&tick) {
+            inputs.extend(local.iter().map(|i| (self.local_slot, *i)));
+        }
+        //no input from the peer yet means we guess they didn't do anything new
+        if let Some(remote) = self.remote_inputs.get(&tick) {
+            inputs.extend(remote.iter().map(|i| (self.remote_slot, *i)));
+        }
+        inputs
+    }
+
+    ///called after a tick has run during a resimulation
+    pub fn end_tick(&mut self, tick: u32) {
+        if self.resim_target == Some(tick + 1) {
+            self.resim_target = None;
+        }
+    }
+
+    //sends every input the peer hasn't acknowledged, up to `last`
+    fn send_inputs(&mut self, last: u32) {
+        //forget inputs the peer has and that no rollback can reach any more
+        let ack = self.remote_ack;
+        let oldest = self.snapshots.front().map_or(self.live_tick, |s| s.tick);
+        self.local_inputs.retain(|t, _| *t >= ack.min(oldest));
+        self.remote_inputs.retain(|t, _| *t >= oldest);
+        let ticks: Vec<Vec<PadInput>> = (ack..=last)
+            .map(|t| self.local_inputs.get(&t).cloned().unwrap_or_default())
+            .collect();
+        let msg = PeerMsg::Inputs{start: ack, ticks, ack: self.remote_confirmed};
+        self.send(&msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LATENCY: Duration = Duration::from_millis(20);
+
+    //two sessions talking over a loopback pair, each direction LATENCY late
+    fn pair() -> (RollbackSession, RollbackSession) {
+        let (a, b) = LoopbackTransport::pair();
+        let delayed = |t| Box::new(DelayedTransport::new(t, LATENCY, Duration::ZERO, 0.0));
+        (RollbackSession::new(delayed(a)), RollbackSession::new(delayed(b)))
+    }
+
+    fn empty_state(tick: u32) -> CombatState {
+        CombatState {
+            tick,
+            players: Vec::new(),
+            weapons: Vec::new(),
+            projectiles: Vec::new(),
+            rng: MatchRng::new(0),
+            stats: MatchStats::default(),
+            hitstop: 0,
+        }
+    }
+
+    //polls both sessions until `done` says so, giving the delayed packets time to land
+    fn poll_until(a: &mut RollbackSession, b: &mut RollbackSession, mut done: impl FnMut(&RollbackSession, &RollbackSession) -> bool)
+        -> (Vec<PeerStart>, Vec<PeerStart>)
+    {
+        let rules = MatchRules::default();
+        let (mut starts_a, mut starts_b) = (Vec::new(), Vec::new());
+        for _ in 0..100 {
+            starts_a.extend(a.poll(&rules));
+            starts_b.extend(b.poll(&rules));
+            if done(a, b) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        (starts_a, starts_b)
+    }
+
+    fn started_pair() -> (RollbackSession, RollbackSession) {
+        let (mut a, mut b) = pair();
+        let rules = MatchRules::default();
+        assert!(a.ready(Class::Barbarian, &rules).is_none());
+        assert!(b.ready(Class::Wizard, &rules).is_none());
+        poll_until(&mut a, &mut b, |a, b| a.is_running() && b.is_running());
+        (a, b)
+    }
+
+    #[test]
+    fn handshake_starts_both_peers_on_the_same_match() {
+        let (mut a, mut b) = pair();
+        let rules = MatchRules::default();
+        a.ready(Class::Barbarian, &rules);
+        b.ready(Class::Wizard, &rules);
+        let (starts_a, starts_b) = poll_until(&mut a, &mut b, |a, b| a.is_running() && b.is_running());
+
+        assert!(a.is_running() && b.is_running());
+        assert_eq!((starts_a.len(), starts_b.len()), (1, 1));
+        assert_eq!(starts_a[0].seed, starts_b[0].seed);
+        //one of us is player 1, and they put their own class in slot 0
+        assert_eq!(a.local_slot + b.local_slot, 1);
+        assert_eq!(a.remote_slot, b.local_slot);
+        let players: Vec<usize> = starts_a[0].players.iter().map(|(slot, _)| *slot).collect();
+        assert_eq!(players, vec![0, 1]);
+    }
+
+    #[test]
+    fn inputs_are_delivered_and_acknowledged() {
+        let (mut a, mut b) = started_pair();
+        let jump = PadInput::Pressed(g::Button::South);
+
+        a.add_local_input(jump);
+        let inputs = a.begin_tick(0, empty_state(0));
+        assert!(inputs.is_empty());
+        poll_until(&mut a, &mut b, |_, b| b.remote_confirmed > INPUT_DELAY);
+
+        //b has a's input for every tick up to the one it takes effect on
+        assert_eq!(b.remote_confirmed, INPUT_DELAY + 1);
+        assert_eq!(b.remote_inputs.get(&INPUT_DELAY), Some(&vec![jump]));
+
+        //b's next Inputs tells a how far it got
+        b.begin_tick(0, empty_state(0));
+        poll_until(&mut a, &mut b, |a, _| a.remote_ack > 0);
+        assert_eq!(a.remote_ack, INPUT_DELAY + 1);
+        assert_eq!(a.remote_confirmed, INPUT_DELAY + 1);
+
+        //and the input turns up on the right tick, in a's slot
+        let on_tick = b.begin_tick(INPUT_DELAY, empty_state(INPUT_DELAY));
+        assert_eq!(on_tick, vec![(b.remote_slot, jump)]);
+    }
+
+    #[test]
+    fn a_late_remote_input_rolls_back_to_its_tick() {
+        let (mut a, mut b) = started_pair();
+        let swing = PadInput::Pressed(g::Button::RightTrigger);
+
+        //b presses a button on tick 1; it takes effect on tick 1 + INPUT_DELAY
+        b.begin_tick(0, empty_state(0));
+        b.add_local_input(swing);
+        b.begin_tick(1, empty_state(1));
+        let late_tick = 1 + INPUT_DELAY;
+
+        //meanwhile a runs ahead, guessing b did nothing
+        for tick in 0..6 {
+            assert!(a.can_advance(tick));
+            a.begin_tick(tick, empty_state(tick));
+        }
+        assert!(a.take_rollback().is_none());
+
+        poll_until(&mut a, &mut b, |a, _| a.rollback_to.is_some());
+        assert_eq!(a.rollback_to, Some(late_tick));
+        let state = a.take_rollback().expect("the mispredicted tick should still be saved");
+        assert_eq!(state.tick, late_tick);
+        //taken once, and the states after it are gone
+        assert!(a.take_rollback().is_none());
+        assert!(a.snapshots.iter().all(|s| s.tick < late_tick));
+    }
+}
@@ -0,0 +1,19 @@
+//this module contains the rules of a match and how it ended
+use crate::*;
+
+//the rules are part of the telemetry format, so they live with it
+pub use telemetry_schema::MatchRules;
+
+//where players start (in player number order) and respawn
+pub const SPAWN_POINTS: [[f32; 3]; 4] = [
+    [6.0, 3.0, 0.0],
+    [-6.0, 3.0, 0.0],
+    [-6.0, -3.0, 0.0],
+    [6.0, -3.0, 0.0],
+];
+
+#[derive(Debug, Clone)]
+pub struct MatchResult {
+    //player number (starting at 0) of the last player standing; None if nobody was
+    pub winner: Option<usize>,
+}
@@ -0,0 +1,23 @@
+//this module contains how the game's own files (options.ron, profiles.ron, the current
+// tournament and replays) are written: as pretty ron, making their folder first if need be.
+// each file is read back by its own load; options, profiles and tournaments fall back to defaults
+// on anything unreadable, replays are turned away (see ReplayError)
+use crate::*;
+use serde::Serialize;
+use std::fs;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Format(String),
+}
+
+///writes `value` to `path` as ron
+pub fn save_ron<T: Serialize>(path: &str, value: &T) -> Result<(), SaveError> {
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir).map_err(SaveError::Io)?;
+    }
+    let text = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|e| SaveError::Format(e.to_string()))?;
+    fs::write(path, text).map_err(SaveError::Io)
+}
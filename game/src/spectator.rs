@@ -0,0 +1,134 @@
+//this module contains spectator mode: watching a match without taking a player slot.
+//a spectator never sends Message::Controller; their pads are ignored and the keyboard drives the camera:
+// Tab follows the next player, 1-4 follow that player
+// the arrow keys pan a free camera (and stop following)
+// Backspace hands the camera back to the rig (see camera.rs), which frames everyone
+// H toggles hitboxes (drawn by the debug overlay in debug.rs), I toggles the stats overlay
+//spectate a LAN match with SHAPEFIGHTS_SPECTATE (see net.rs), or press F8 to spectate locally,
+// e.g. while watching a replay
+use crate::*;
+use fyrox::keyboard::KeyCode;
+
+pub struct Spectator {
+    //the slot being followed; None is the free camera, or the rig if we haven't panned
+    pub follow: Option<usize>,
+    pub hitboxes: bool,
+    pub stats_visible: bool,
+    stats: Handle<UiNode>,
+    //where the free camera is, once we've let go of a player
+    position: Option<Vector2<f32>>,
+    //arrow keys held: left, right, up, down
+    pan: [bool; 4],
+}
+
+impl Spectator {
+    //world units the free camera moves per frame
+    const PAN_SPEED: f32 = 0.15;
+
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let stats = create_text_with_background(ui, "", 10.0, 400.0, Color::opaque(255, 255, 255));
+        set_text_visibility(ui, stats, true);
+        Self {
+            follow: None,
+            hitboxes: false,
+            stats_visible: true,
+            stats,
+            position: None,
+            pan: [false; 4],
+        }
+    }
+
+    pub fn remove(&self, ui: &mut UserInterface) {
+        let border = ui.node(self.stats).parent();
+        ui.send_message(WidgetMessage::remove(border, MessageDirection::ToWidget));
+    }
+
+    ///handles a key going down or up; `players` are the slots we can follow
+    pub fn on_key(&mut self, key: KeyCode, pressed: bool, players: &BTreeMap<usize, Handle<Node>>, ui: &mut UserInterface) {
+        let pan = match key {
+            KeyCode::ArrowLeft => Some(0),
+            KeyCode::ArrowRight => Some(1),
+            KeyCode::ArrowUp => Some(2),
+            KeyCode::ArrowDown => Some(3),
+            _ => None,
+        };
+        if let Some(i) = pan {
+            self.pan[i] = pressed;
+            return;
+        }
+        if !pressed {
+            return;
+        }
+
+        match key {
+            //the next slot after the one we're following, wrapping around
+            KeyCode::Tab => {
+                self.follow = match self.follow {
+                    Some(current) => players.range(current + 1..).next().or(players.iter().next()),
+                    None => players.iter().next(),
+                }.map(|(slot, _)| *slot);
+            },
+            KeyCode::Digit1 | KeyCode::Digit2 | KeyCode::Digit3 | KeyCode::Digit4 => {
+                let slot = match key {
+                    KeyCode::Digit1 => 0,
+                    KeyCode::Digit2 => 1,
+                    KeyCode::Digit3 => 2,
+                    _ => 3,
+                };
+                if players.contains_key(&slot) {
+                    self.follow = Some(slot);
+                }
+            },
+            KeyCode::Backspace => {
+                self.follow = None;
+                self.position = None;
+            },
+            KeyCode::KeyH => self.hitboxes = !self.hitboxes,
+            KeyCode::KeyI => {
+                self.stats_visible = !self.stats_visible;
+                set_text_visibility(ui, self.stats, self.stats_visible);
+            },
+            _ => (),
+        }
+    }
+
+    ///rebuilds the stats overlay; called once per frame. returns where the camera
+    /// should look, or None to let the rig frame everyone. `current` is where it's looking now
+    pub fn update(&mut self, scene: &mut Scene, ui: &mut UserInterface, players: &BTreeMap<usize, Handle<Node>>, stats: &MatchStats, current: Vector2<f32>) -> Option<Vector2<f32>> {
+        //panning lets go of whoever we were following
+        let dir = Vector2::new(
+            self.pan[1] as i32 as f32 - self.pan[0] as i32 as f32,
+            self.pan[2] as i32 as f32 - self.pan[3] as i32 as f32,
+        );
+        if dir.x != 0.0 || dir.y != 0.0 {
+            self.follow = None;
+            *self.position.get_or_insert(current) += dir * Self::PAN_SPEED;
+        }
+
+        let focus = match self.follow.and_then(|slot| players.get(&slot)).and_then(|h| scene.graph.try_get(*h)) {
+            Some(player) => {
+                let at = player.global_position();
+                self.position = None;
+                Some(Vector2::new(at.x, at.y))
+            },
+            None => self.position,
+        };
+
+        if !self.stats_visible {
+            return focus;
+        }
+        let mut text = String::new();
+        for (slot, handle) in players {
+            let class = match scene.graph.try_get(*handle).and_then(|n| n.try_get_script::<Player>()) {
+                Some(player) => format!("{:?}", player.class),
+                None => continue,
+            };
+            let s = stats.get(*handle);
+            let marker = if self.follow == Some(*slot) {">"} else {" "};
+            text += &format!("{marker} P{} {class}: dealt {} taken {} kills {} deaths {} parries {} projectiles {}\n",
+                slot + 1, s.damage_dealt, s.damage_taken, s.kills, s.deaths, s.parries, s.projectiles);
+        }
+        ui.send_message(TextMessage::text(self.stats, MessageDirection::ToWidget, text));
+        focus
+    }
+}
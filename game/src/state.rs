@@ -0,0 +1,180 @@
+//this module contains the player state machine:
+// the PlayerState enum itself
+// the table of which transitions are legal
+// the enter/exit hooks that run when the player changes state
+// the log of recent transitions, for debugging
+use crate::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Visit, Reflect, Debug, Clone, Default, Serialize, Deserialize)]
+pub enum PlayerState {
+    #[default]
+    Idle,
+    Charging,
+    Dead(i32),
+    Riposting,
+    //the field holds the number of frames the player is into the action
+    Attacking(i32),
+    Hit(i32),
+    Parry(i32),
+}
+
+//This makes it so that comparing player states ignores the value of the frame
+impl PartialEq for PlayerState {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PlayerState::Idle, PlayerState::Idle)  => true,
+            (PlayerState::Charging, PlayerState::Charging) => true,
+            (PlayerState::Dead(_), PlayerState::Dead(_))  => true,
+            (PlayerState::Riposting, PlayerState::Riposting)  => true,
+            (PlayerState::Attacking(_), PlayerState::Attacking(_))  => true,
+            (PlayerState::Hit(_), PlayerState::Hit(_))  => true,
+            (PlayerState::Parry(_), PlayerState::Parry(_))  => true,
+            _ => false
+        }
+    }
+}
+
+impl PlayerState {
+    pub fn is_dead(&self) -> bool {
+        matches!(self, PlayerState::Dead(_))
+    }
+
+    ///checks the transition table: can a player in this state move into `next`?
+    /// frame advances within a state don't come through here; see Player::advance
+    pub fn can_transition(&self, next: &PlayerState) -> bool {
+        use PlayerState::*;
+        match (self, next) {
+            //the only way out of death is respawning
+            (Dead(_), Idle) => true,
+            (Dead(_), _) => false,
+            //actions can't be restarted partway through
+            (Attacking(_), Attacking(_)) | (Parry(_), Parry(_)) => false,
+            //any living player can be hit or killed
+            (_, Hit(_)) | (_, Dead(_)) => true,
+            //idle players can start anything
+            (Idle, _) => true,
+            //barbarians can swing out of a charge
+            (Charging, Attacking(_)) => true,
+            //every action ends by going back to idle
+            (_, Idle) => true,
+            _ => false,
+        }
+    }
+}
+
+///one entry in a player's transition log
+#[derive(Visit, Reflect, Debug, Clone, Default)]
+pub struct StateTransition {
+    pub from: PlayerState,
+    pub to: PlayerState,
+    //false if the transition table rejected this transition
+    pub accepted: bool,
+}
+
+impl Player {
+    //how many transitions each player remembers
+    pub const TRANSITION_LOG_LEN: usize = 32;
+
+    ///moves the player into `next` if the transition table allows it, running the exit hook of
+    /// the old state and the enter hook of the new one. returns whether the transition happened.
+    pub fn transition(&mut self, next: PlayerState, sender: &ScriptMessageSender) -> bool {
+        let accepted = self.state.can_transition(&next);
+        self.record_transition(StateTransition{from: self.state.clone(), to: next.clone(), accepted});
+        if !accepted {
+            return false;
+        }
+
+        let prev = std::mem::replace(&mut self.state, next);
+        self.on_exit(&prev, sender);
+        self.on_enter(sender);
+        true
+    }
+
+    ///advances the frame counter of the current state, skipping the table, the hooks and the
+    /// log. anything that isn't the same state as the current one is ignored
+    pub fn advance(&mut self, next: PlayerState) {
+        if self.state == next {
+            self.state = next;
+        }
+    }
+
+    fn record_transition(&mut self, t: StateTransition) {
+        if self.transitions.len() >= Self::TRANSITION_LOG_LEN {
+            self.transitions.remove(0);
+        }
+        self.transitions.push(t);
+    }
+
+    ///runs when the player leaves `prev`
+    fn on_exit(&mut self, prev: &PlayerState, sender: &ScriptMessageSender) {
+        match prev {
+            //leaving any weapon-using state always puts the weapon away
+            PlayerState::Attacking(_) | PlayerState::Parry(_) | PlayerState::Riposting => {
+                sender.send_to_target(self.weapon, Message::Attack{s: false});
+            },
+            _ => (),
+        }
+    }
+
+    ///runs when the player enters its current state
+    fn on_enter(&mut self, sender: &ScriptMessageSender) {
+        match self.state {
+            PlayerState::Attacking(_) => {
+                sender.send_to_target(self.weapon, Message::Attack{s: true});
+            },
+            PlayerState::Parry(_) => {
+                sender.send_to_target(self.weapon, Message::Start_Parry{});
+            },
+            _ => (),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::PlayerState::*;
+
+    #[test]
+    fn dead_players_only_respawn() {
+        assert!(!Dead(3).can_transition(&Attacking(1)));
+        assert!(!Dead(3).can_transition(&Parry(0)));
+        assert!(!Dead(3).can_transition(&Hit(0)));
+        assert!(!Dead(3).can_transition(&Charging));
+        assert!(Dead(1).can_transition(&Idle));
+    }
+
+    #[test]
+    fn living_players_can_always_be_hit() {
+        for state in [Idle, Charging, Riposting, Attacking(4), Hit(2), Parry(6)] {
+            assert!(state.can_transition(&Hit(0)), "{state:?} -> Hit");
+            assert!(state.can_transition(&Dead(10)), "{state:?} -> Dead");
+        }
+    }
+
+    #[test]
+    fn actions_cant_restart_midway() {
+        assert!(!Attacking(5).can_transition(&Attacking(1)));
+        assert!(!Parry(5).can_transition(&Parry(0)));
+        assert!(!Charging.can_transition(&Charging));
+    }
+
+    #[test]
+    fn actions_start_from_idle_and_end_in_idle() {
+        assert!(Idle.can_transition(&Attacking(1)));
+        assert!(Idle.can_transition(&Parry(0)));
+        assert!(Idle.can_transition(&Charging));
+        assert!(Attacking(27).can_transition(&Idle));
+        assert!(Parry(28).can_transition(&Idle));
+        assert!(Hit(15).can_transition(&Idle));
+    }
+
+    #[test]
+    fn only_charges_cancel_into_attacks() {
+        assert!(Charging.can_transition(&Attacking(1)));
+        assert!(!Hit(3).can_transition(&Attacking(1)));
+        assert!(!Parry(3).can_transition(&Attacking(1)));
+        assert!(!Attacking(3).can_transition(&Parry(0)));
+    }
+}
@@ -0,0 +1,40 @@
+//this module contains the per-match statistics, tallied from GameEvents
+use crate::*;
+
+//a player's stats are part of the telemetry format, so they live with it
+pub use telemetry_schema::PlayerStats;
+
+#[derive(Debug, Clone, Default)]
+pub struct MatchStats {
+    pub players: HashMap<Handle<Node>, PlayerStats>,
+}
+
+impl MatchStats {
+    pub fn get(&self, player: Handle<Node>) -> PlayerStats {
+        self.players.get(&player).cloned().unwrap_or_default()
+    }
+
+    fn entry(&mut self, player: Handle<Node>) -> &mut PlayerStats {
+        self.players.entry(player).or_default()
+    }
+
+    pub fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::PlayerDamaged{player, attacker, damage, ..} => {
+                self.entry(*player).damage_taken += damage;
+                if attacker.is_some() {
+                    self.entry(*attacker).damage_dealt += damage;
+                }
+            },
+            GameEvent::PlayerDied{player, killer, ..} => {
+                self.entry(*player).deaths += 1;
+                if killer.is_some() && killer != player {
+                    self.entry(*killer).kills += 1;
+                }
+            },
+            GameEvent::ParrySucceeded{player, ..} => self.entry(*player).parries += 1,
+            GameEvent::ProjectileFired{player, ..} => self.entry(*player).projectiles += 1,
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//this module contains the telemetry sink: every gameplay event of every match, written as one JSON
+// object per line to telemetry/events.jsonl, which is appended to so it builds up over many matches.
+//every line has the match id (the match's seed), the tick, the unix time and an "event" field:
+// match_started, hit, parry_started, parry, death, respawn, projectile_fired and match_ended.
+// the analytics tool (cargo run --package analytics) reads these back. the line format itself
+// lives in the telemetry-schema crate, so the tool doesn't have to build the game to read it
+// players are written as their slot, class name and whether they're a bot; scene handles mean
+// nothing outside the game
+//SHAPEFIGHTS_TELEMETRY sets a different file, or turns it off with "off".
+//only matches this game actually simulates are recorded: not replays being played back, not LAN
+// clients, not training, and not rollback matches (both peers would record the same match).
+use crate::*;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub use telemetry_schema::{PlayerInfo, TelemetryEvent, TelemetryLine};
+
+#[derive(Default)]
+pub struct Telemetry {
+    //where to write; None turns telemetry off
+    pub path: Option<String>,
+    file: Option<BufWriter<File>>,
+    //the match being recorded, and who's in it
+    match_id: Option<u64>,
+    roster: HashMap<Handle<Node>, PlayerInfo>,
+}
+
+impl Telemetry {
+    pub const DEFAULT_PATH: &'static str = "telemetry/events.jsonl";
+
+    ///reads SHAPEFIGHTS_TELEMETRY
+    pub fn from_env() -> Self {
+        let path = match std::env::var("SHAPEFIGHTS_TELEMETRY") {
+            Ok(path) if path == "off" => None,
+            Ok(path) => Some(path),
+            Err(_) => Some(Self::DEFAULT_PATH.to_string()),
+        };
+        Self{path, ..Default::default()}
+    }
+
+    ///starts recording a match. `roster` is every player in it
+    pub fn start(&mut self, match_id: u64, rules: &MatchRules, roster: HashMap<Handle<Node>, PlayerInfo>) {
+        if self.path.is_none() {
+            return;
+        }
+        self.match_id = Some(match_id);
+        self.roster = roster;
+        let mut players: Vec<PlayerInfo> = self.roster.values().cloned().collect();
+        players.sort_by_key(|p| p.slot);
+        self.write(0, TelemetryEvent::MatchStarted{rules: rules.clone(), players});
+    }
+
+    ///records a gameplay event, if we're recording a match
+    pub fn on_event(&mut self, event: &GameEvent, tick: u32) {
+        if self.match_id.is_none() {
+            return;
+        }
+        let info = |h: &Handle<Node>| self.roster.get(h).cloned();
+        let at = |p: &Vector3<f32>| [p.x, p.y];
+        let line = match event {
+            GameEvent::PlayerDamaged{player, attacker, damage, position} => match info(player) {
+                Some(victim) => TelemetryEvent::Hit{attacker: info(attacker), victim, damage: *damage, position: at(position)},
+                None => return,
+            },
+            GameEvent::ParryStarted{player} => match info(player) {
+                Some(player) => TelemetryEvent::ParryStarted{player},
+                None => return,
+            },
+            GameEvent::ParrySucceeded{player, attacker, position} => match info(player) {
+                Some(player) => TelemetryEvent::Parry{player, attacker: info(attacker), position: at(position)},
+                None => return,
+            },
+            GameEvent::PlayerDied{player, killer, position} => match info(player) {
+                Some(player) => TelemetryEvent::Death{player, killer: info(killer), position: at(position)},
+                None => return,
+            },
+            GameEvent::PlayerRespawned{player, position} => match info(player) {
+                Some(player) => TelemetryEvent::Respawn{player, position: at(position)},
+                None => return,
+            },
+            GameEvent::ProjectileFired{player, ..} => match info(player) {
+                Some(player) => TelemetryEvent::ProjectileFired{player},
+                None => return,
+            },
+            GameEvent::PlayerChanged{..} => return,
+        };
+        self.write(tick, line);
+    }
+
+    ///records how the match ended and stops recording. `winner` is a slot
+    pub fn finish(&mut self, tick: u32, winner: Option<usize>, completed: bool, stats: &MatchStats) {
+        if self.match_id.is_none() {
+            return;
+        }
+        let mut players: Vec<(PlayerInfo, PlayerStats)> = self.roster.iter()
+            .map(|(handle, info)| (info.clone(), stats.get(*handle)))
+            .collect();
+        players.sort_by_key(|(p, _)| p.slot);
+        let winner = players.iter().map(|(p, _)| p).find(|p| Some(p.slot) == winner).cloned();
+        self.write(tick, TelemetryEvent::MatchEnded{winner, completed, players});
+
+        if let Some(file) = &mut self.file {
+            if let Err(e) = file.flush() {
+                warn!("couldn't write telemetry: {e}");
+            }
+        }
+        self.match_id = None;
+        self.roster.clear();
+    }
+
+    fn write(&mut self, tick: u32, event: TelemetryEvent) {
+        let (match_id, path) = match (self.match_id, &self.path) {
+            (Some(match_id), Some(path)) => (match_id, path),
+            _ => return,
+        };
+        if self.file.is_none() {
+            self.file = match open(path) {
+                Ok(file) => Some(BufWriter::new(file)),
+                Err(e) => {
+                    //don't keep trying every event
+                    error!("couldn't open telemetry file {path}, turning telemetry off: {e}");
+                    self.path = None;
+                    return;
+                },
+            };
+        }
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |t| t.as_secs());
+        let line = TelemetryLine{match_id, tick, time, event};
+        if let Some(file) = &mut self.file {
+            let written = serde_json::to_string(&line)
+                .map_err(|e| e.to_string())
+                .and_then(|json| writeln!(file, "{json}").map_err(|e| e.to_string()));
+            if let Err(e) = written {
+                warn!("couldn't write telemetry: {e}");
+            }
+        }
+    }
+}
+
+//opens the telemetry file for appending, making its folder if need be
+fn open(path: &str) -> std::io::Result<File> {
+    if let Some(dir) = Path::new(path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
@@ -0,0 +1,492 @@
+//this module contains tournament brackets, for running the weekly tournament in the game:
+// single or double elimination brackets between player profiles (see profiles.rs), seeded in the
+//  order the profiles were entered, with byes if the entrants don't fill the bracket
+// every set is best-of-N games, played with the tournament's match rules
+// results are recorded when a game ends (see Game::check_match_over), and the bracket is saved to
+//  tournaments/current.ron after every game so a crash doesn't lose it
+//double elimination has a winners bracket, a losers bracket and one grand final (no bracket reset).
+//F11 in the lobby opens the setup (or picks a saved tournament back up): up/down picks a profile,
+// space enters or leaves it, D switches single/double, left/right changes best-of, enter starts.
+//between sets the bracket is shown in the lobby with the next set's players already on P1 and P2;
+// they pick classes and press start. enter goes back to the lobby once a game is over.
+use crate::*;
+use fyrox::keyboard::KeyCode;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BracketKind {
+    Single,
+    Double,
+}
+
+///where one side of a set comes from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Feed {
+    Entrant(usize),
+    Bye,
+    Winner(usize),
+    Loser(usize),
+}
+
+///who's on one side of a set, as far as we know yet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Entrant(usize),
+    Bye,
+    //waiting on another set
+    Pending,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Set {
+    //e.g. "Winners round 1"
+    pub round: String,
+    pub feeds: [Feed; 2],
+    //games won by each side
+    pub wins: [u32; 2],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tournament {
+    pub kind: BracketKind,
+    pub best_of: u32,
+    pub rules: MatchRules,
+    //profile names, in seed order
+    pub entrants: Vec<String>,
+    //in the order they're played; a set only ever feeds from sets before it
+    pub sets: Vec<Set>,
+}
+
+impl Tournament {
+    pub const PATH: &'static str = "tournaments/current.ron";
+
+    pub fn new(kind: BracketKind, best_of: u32, rules: MatchRules, entrants: Vec<String>) -> Self {
+        let mut tournament = Self{kind, best_of, rules, entrants, sets: Vec::new()};
+        let winners = tournament.winners_bracket();
+        if kind == BracketKind::Double && winners.len() > 1 {
+            let losers_final = tournament.losers_bracket(&winners);
+            let winners_final = *winners.last().and_then(|r| r.first()).unwrap();
+            tournament.add("Grand final", [Feed::Winner(winners_final), Feed::Winner(losers_final)]);
+        }
+        tournament
+    }
+
+    fn add(&mut self, round: &str, feeds: [Feed; 2]) -> usize {
+        self.sets.push(Set{round: round.to_string(), feeds, wins: [0, 0]});
+        self.sets.len() - 1
+    }
+
+    //the winners bracket (the whole bracket for single elimination), returning each round's sets
+    fn winners_bracket(&mut self) -> Vec<Vec<usize>> {
+        let size = self.entrants.len().max(2).next_power_of_two();
+        let feeds: Vec<Feed> = seed_order(size).into_iter()
+            .map(|seed| if seed < self.entrants.len() {Feed::Entrant(seed)} else {Feed::Bye})
+            .collect();
+        let name = match self.kind {
+            BracketKind::Single => "Round",
+            BracketKind::Double => "Winners round",
+        };
+
+        let mut rounds = Vec::new();
+        let mut feeds = feeds;
+        while feeds.len() > 1 {
+            let round = format!("{name} {}", rounds.len() + 1);
+            let sets: Vec<usize> = feeds.chunks(2).map(|pair| self.add(&round, [pair[0], pair[1]])).collect();
+            feeds = sets.iter().map(|s| Feed::Winner(*s)).collect();
+            rounds.push(sets);
+        }
+        rounds
+    }
+
+    //the losers bracket, fed by the winners bracket's rounds; returns the losers final
+    fn losers_bracket(&mut self, winners: &[Vec<usize>]) -> usize {
+        let mut round = 0;
+        let mut name = || {
+            round += 1;
+            format!("Losers round {round}")
+        };
+
+        //the first round's losers play each other
+        let first = name();
+        let mut survivors: Vec<Feed> = winners[0].chunks(2)
+            .map(|pair| match pair {
+                [a, b] => Feed::Winner(self.add(&first, [Feed::Loser(*a), Feed::Loser(*b)])),
+                _ => Feed::Loser(pair[0]),
+            })
+            .collect();
+
+        for dropping in &winners[1..] {
+            //losers dropping down play the survivors, reversed so early rematches are less likely
+            let drop = name();
+            survivors = survivors.iter().zip(dropping.iter().rev())
+                .map(|(survivor, loser)| Feed::Winner(self.add(&drop, [*survivor, Feed::Loser(*loser)])))
+                .collect();
+            //then the survivors play each other, until there's one left
+            if survivors.len() > 1 {
+                let inner = name();
+                survivors = survivors.chunks(2)
+                    .map(|pair| Feed::Winner(self.add(&inner, [pair[0], pair[1]])))
+                    .collect();
+            }
+        }
+        match survivors[0] {
+            Feed::Winner(set) => set,
+            //only happens with a two entrant bracket, which has no losers bracket
+            _ => unreachable!(),
+        }
+    }
+
+    ///sets are one on one: whoever's picked a class on P3 or P4 doesn't play
+    pub fn seat(classes: &mut BTreeMap<usize, Class>) {
+        classes.retain(|slot, _| *slot < 2);
+    }
+
+    ///games needed to win a set
+    pub fn to_win(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    ///who's on one side of a set
+    pub fn side(&self, feed: Feed) -> Side {
+        match feed {
+            Feed::Entrant(i) => Side::Entrant(i),
+            Feed::Bye => Side::Bye,
+            Feed::Winner(set) => self.outcome(set).map_or(Side::Pending, |(winner, _)| winner),
+            Feed::Loser(set) => self.outcome(set).map_or(Side::Pending, |(_, loser)| loser),
+        }
+    }
+
+    ///the winner and loser of a set, once it's decided. byes lose to anyone without playing
+    pub fn outcome(&self, set: usize) -> Option<(Side, Side)> {
+        let s = &self.sets[set];
+        let (a, b) = (self.side(s.feeds[0]), self.side(s.feeds[1]));
+        match (a, b) {
+            (Side::Pending, _) | (_, Side::Pending) => None,
+            (Side::Bye, _) => Some((b, a)),
+            (_, Side::Bye) => Some((a, b)),
+            _ if s.wins[0] >= self.to_win() => Some((a, b)),
+            _ if s.wins[1] >= self.to_win() => Some((b, a)),
+            _ => None,
+        }
+    }
+
+    ///the next set to play and its two entrants, or None once the tournament is over
+    pub fn next_set(&self) -> Option<(usize, usize, usize)> {
+        (0..self.sets.len()).find_map(|set| {
+            match (self.side(self.sets[set].feeds[0]), self.side(self.sets[set].feeds[1])) {
+                (Side::Entrant(a), Side::Entrant(b)) if self.outcome(set).is_none() => Some((set, a, b)),
+                _ => None,
+            }
+        })
+    }
+
+    ///the winner of the whole thing
+    pub fn champion(&self) -> Option<&str> {
+        match self.outcome(self.sets.len().checked_sub(1)?)? {
+            (Side::Entrant(i), _) => Some(&self.entrants[i]),
+            _ => None,
+        }
+    }
+
+    ///records a game of the next set; `side` is 0 if the player on P1 won, 1 for P2
+    pub fn record_game(&mut self, side: usize) {
+        if let Some((set, _, _)) = self.next_set() {
+            self.sets[set].wins[side] += 1;
+            info!("{}: {} to {}", self.sets[set].round, self.sets[set].wins[0], self.sets[set].wins[1]);
+        }
+    }
+
+    pub fn load() -> Option<Self> {
+        let text = fs::read_to_string(Self::PATH).ok()?;
+        ron::from_str(&text).map_err(|e| warn!("couldn't read {}: {e}", Self::PATH)).ok()
+    }
+
+    pub fn save(&self) -> Result<(), SaveError> {
+        save_ron(Self::PATH, self)
+    }
+
+    ///the bracket as text, a line per set
+    pub fn describe(&self) -> String {
+        let name = |side: Side| match side {
+            Side::Entrant(i) => self.entrants[i].clone(),
+            Side::Bye => "(bye)".to_string(),
+            Side::Pending => "...".to_string(),
+        };
+        let next = self.next_set().map(|(set, _, _)| set);
+        let mut text = format!("TOURNAMENT ({:?} elimination, best of {})\n", self.kind, self.best_of);
+        for (i, set) in self.sets.iter().enumerate() {
+            let (a, b) = (self.side(set.feeds[0]), self.side(set.feeds[1]));
+            //sets decided by a bye aren't worth showing
+            if a == Side::Bye || b == Side::Bye {
+                continue;
+            }
+            let marker = if next == Some(i) {">"} else {" "};
+            text += &format!("\n{marker} {}: {} {}-{} {}", set.round, name(a), set.wins[0], set.wins[1], name(b));
+        }
+        match (self.champion(), self.next_set()) {
+            (Some(champion), _) => text += &format!("\n\n{champion} wins the tournament!"),
+            (None, Some((_, a, b))) => text += &format!("\n\nnext: {} (P1) vs {} (P2); pick classes and start", self.entrants[a], self.entrants[b]),
+            (None, None) => (),
+        }
+        text
+    }
+}
+
+//the order seeds go into the first round so the top seeds meet last: 0, 7, 3, 4, 1, 6, 2, 5 for 8
+fn seed_order(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let n = order.len() * 2;
+        order = order.iter().flat_map(|s| [*s, n - 1 - s]).collect();
+    }
+    order
+}
+
+///the setup screen, picking who's in and the format
+pub struct TournamentSetup {
+    //whether each profile is entered, by index into Profiles::profiles
+    entered: Vec<bool>,
+    row: usize,
+    kind: BracketKind,
+    best_of: u32,
+    text: Handle<UiNode>,
+}
+
+impl TournamentSetup {
+    const MAX_BEST_OF: u32 = 7;
+    //lives per player when the match rules would have them respawn forever
+    const STOCKS: u32 = 3;
+
+    pub fn new(ui: &mut UserInterface, profiles: &Profiles) -> Self {
+        let text = create_text_with_background(ui, "", 300.0, 100.0, Color::opaque(255, 255, 255));
+        set_text_visibility(ui, text, true);
+        let setup = Self{
+            entered: vec![true; profiles.profiles.len()],
+            row: 0,
+            kind: BracketKind::Single,
+            best_of: 3,
+            text,
+        };
+        setup.refresh(ui, profiles);
+        setup
+    }
+
+    pub fn remove(self, ui: &mut UserInterface) {
+        let border = ui.node(self.text).parent();
+        ui.send_message(WidgetMessage::remove(border, MessageDirection::ToWidget));
+    }
+
+    ///handles a key press; returns the tournament when enter starts one
+    pub fn on_key(&mut self, key: KeyCode, profiles: &Profiles, rules: &MatchRules, ui: &mut UserInterface) -> Option<Tournament> {
+        let rows = self.entered.len().max(1);
+        match key {
+            KeyCode::ArrowUp => self.row = (self.row + rows - 1) % rows,
+            KeyCode::ArrowDown => self.row = (self.row + 1) % rows,
+            KeyCode::Space => if let Some(entered) = self.entered.get_mut(self.row) {
+                *entered = !*entered;
+            },
+            KeyCode::KeyD => self.kind = match self.kind {
+                BracketKind::Single => BracketKind::Double,
+                BracketKind::Double => BracketKind::Single,
+            },
+            //best of odd numbers only
+            KeyCode::ArrowLeft => self.best_of = self.best_of.saturating_sub(2).max(1),
+            KeyCode::ArrowRight => self.best_of = (self.best_of + 2).min(Self::MAX_BEST_OF),
+            KeyCode::Enter => {
+                let entrants: Vec<String> = profiles.profiles.iter().zip(&self.entered)
+                    .filter(|(_, entered)| **entered)
+                    .map(|(p, _)| p.name.clone())
+                    .collect();
+                if entrants.len() < 2 {
+                    warn!("a tournament needs at least two profiles entered");
+                } else {
+                    //games have to end for sets to, so endless respawning gets a stock limit
+                    let mut rules = rules.clone();
+                    rules.stocks = rules.stocks.or(Some(Self::STOCKS));
+                    return Some(Tournament::new(self.kind, self.best_of, rules, entrants));
+                }
+            },
+            _ => (),
+        }
+        self.refresh(ui, profiles);
+        None
+    }
+
+    fn refresh(&self, ui: &mut UserInterface, profiles: &Profiles) {
+        let mut text = format!(
+            "NEW TOURNAMENT (F11 to close)\n{:?} elimination (D), best of {} (left/right)\nspace enters a profile, enter starts\n",
+            self.kind, self.best_of);
+        if profiles.profiles.is_empty() {
+            text += "\nno profiles yet; add some in the lobby first";
+        }
+        for (i, (profile, entered)) in profiles.profiles.iter().zip(&self.entered).enumerate() {
+            let cursor = if i == self.row {">"} else {" "};
+            let check = if *entered {"[x]"} else {"[ ]"};
+            text += &format!("\n{cursor} {check} {}", profile.name);
+        }
+        ui.send_message(TextMessage::text(self.text, MessageDirection::ToWidget, text));
+    }
+}
+
+///a tournament being played, and the bracket shown between sets
+pub struct TournamentMode {
+    pub tournament: Tournament,
+    bracket: Handle<UiNode>,
+}
+
+impl TournamentMode {
+    pub fn new(ui: &mut UserInterface, tournament: Tournament) -> Self {
+        let bracket = create_text_with_background(ui, "", 20.0, 200.0, Color::opaque(255, 255, 255));
+        let mode = Self{tournament, bracket};
+        mode.show_bracket(ui, true);
+        mode
+    }
+
+    pub fn show_bracket(&self, ui: &mut UserInterface, visible: bool) {
+        ui.send_message(TextMessage::text(self.bracket, MessageDirection::ToWidget, self.tournament.describe()));
+        set_text_visibility(ui, self.bracket, visible);
+    }
+
+    pub fn remove(self, ui: &mut UserInterface) {
+        let border = ui.node(self.bracket).parent();
+        ui.send_message(WidgetMessage::remove(border, MessageDirection::ToWidget));
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = self.tournament.save() {
+            error!("couldn't save the tournament: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tournament(kind: BracketKind, best_of: u32, entrants: usize) -> Tournament {
+        let names = (0..entrants).map(|i| format!("player {i}")).collect();
+        Tournament::new(kind, best_of, MatchRules::default(), names)
+    }
+
+    fn rounds(t: &Tournament) -> Vec<&str> {
+        t.sets.iter().map(|s| s.round.as_str()).collect()
+    }
+
+    //plays every set to the end with P1 winning each game; returns the games played
+    fn play_out(t: &mut Tournament) -> usize {
+        let mut games = 0;
+        while t.next_set().is_some() {
+            t.record_game(0);
+            games += 1;
+        }
+        games
+    }
+
+    #[test]
+    fn top_seeds_meet_last() {
+        assert_eq!(seed_order(1), vec![0]);
+        assert_eq!(seed_order(2), vec![0, 1]);
+        assert_eq!(seed_order(8), vec![0, 7, 3, 4, 1, 6, 2, 5]);
+    }
+
+    #[test]
+    fn three_entrants_give_the_top_seed_a_bye() {
+        let mut t = tournament(BracketKind::Single, 1, 3);
+        assert_eq!(rounds(&t), vec!["Round 1", "Round 1", "Round 2"]);
+        assert_eq!(t.sets[0].feeds, [Feed::Entrant(0), Feed::Bye]);
+        assert_eq!(t.outcome(0), Some((Side::Entrant(0), Side::Bye)));
+        //the bye is never played
+        assert_eq!(t.next_set(), Some((1, 1, 2)));
+        assert_eq!(play_out(&mut t), 2);
+        assert_eq!(t.champion(), Some("player 0"));
+    }
+
+    #[test]
+    fn five_entrants_fill_an_eight_bracket_with_byes() {
+        let mut t = tournament(BracketKind::Single, 1, 5);
+        assert_eq!(t.sets.len(), 7);
+        let byes = t.sets.iter().filter(|s| s.feeds.contains(&Feed::Bye)).count();
+        assert_eq!(byes, 3);
+        assert_eq!(t.next_set(), Some((1, 3, 4)));
+        //one game per entrant knocked out
+        assert_eq!(play_out(&mut t), 4);
+        assert_eq!(t.champion(), Some("player 0"));
+    }
+
+    #[test]
+    fn four_entrant_double_elimination() {
+        let mut t = tournament(BracketKind::Double, 1, 4);
+        assert_eq!(rounds(&t), vec![
+            "Winners round 1", "Winners round 1", "Winners round 2",
+            "Losers round 1", "Losers round 2", "Grand final",
+        ]);
+        //the first round's losers meet, then the winner plays whoever loses the winners final
+        assert_eq!(t.sets[3].feeds, [Feed::Loser(0), Feed::Loser(1)]);
+        assert_eq!(t.sets[4].feeds, [Feed::Winner(3), Feed::Loser(2)]);
+        assert_eq!(t.sets[5].feeds, [Feed::Winner(2), Feed::Winner(4)]);
+
+        //everyone but the champion loses twice, and the champion never did
+        assert_eq!(play_out(&mut t), 6);
+        assert_eq!(t.champion(), Some("player 0"));
+    }
+
+    #[test]
+    fn the_losers_bracket_can_win_the_grand_final() {
+        let mut t = tournament(BracketKind::Double, 1, 4);
+        while t.next_set().map(|(set, _, _)| set) != Some(5) {
+            t.record_game(0);
+        }
+        //player 3 lost to player 0 in round 1 and came back through the losers bracket
+        assert_eq!(t.next_set(), Some((5, 0, 3)));
+        t.record_game(1);
+        assert_eq!(t.champion(), Some("player 3"));
+        assert_eq!(t.next_set(), None);
+    }
+
+    #[test]
+    fn eight_entrant_double_elimination() {
+        let mut t = tournament(BracketKind::Double, 1, 8);
+        assert_eq!(t.sets.len(), 14);
+        let losers: Vec<&str> = rounds(&t).into_iter().filter(|r| r.starts_with("Losers")).collect();
+        assert_eq!(losers, vec![
+            "Losers round 1", "Losers round 1", "Losers round 2", "Losers round 2",
+            "Losers round 3", "Losers round 4",
+        ]);
+        //losers dropping from winners round 2 are crossed over against the survivors
+        assert_eq!(t.sets[9].feeds, [Feed::Winner(7), Feed::Loser(5)]);
+        assert_eq!(t.sets[10].feeds, [Feed::Winner(8), Feed::Loser(4)]);
+        //the loser of the winners final drops into the losers final
+        assert_eq!(t.sets[12].feeds, [Feed::Winner(11), Feed::Loser(6)]);
+        assert_eq!(t.sets[13].round, "Grand final");
+        assert_eq!(t.sets[13].feeds, [Feed::Winner(6), Feed::Winner(12)]);
+
+        assert_eq!(play_out(&mut t), 14);
+        assert_eq!(t.champion(), Some("player 0"));
+    }
+
+    #[test]
+    fn only_p1_and_p2_play_a_set() {
+        let mut classes: BTreeMap<usize, Class> = (0..4).map(|slot| (slot, Class::Fighter)).collect();
+        Tournament::seat(&mut classes);
+        assert_eq!(classes.keys().cloned().collect::<Vec<_>>(), vec![0, 1]);
+    }
+
+    #[test]
+    fn best_of_three_needs_two_wins() {
+        let mut t = tournament(BracketKind::Single, 3, 2);
+        assert_eq!(t.to_win(), 2);
+        t.record_game(0);
+        assert_eq!(t.outcome(0), None);
+        t.record_game(1);
+        assert_eq!(t.outcome(0), None);
+        assert_eq!(t.sets[0].wins, [1, 1]);
+        t.record_game(1);
+        assert_eq!(t.outcome(0), Some((Side::Entrant(1), Side::Entrant(0))));
+        assert_eq!(t.champion(), Some("player 1"));
+
+        //games after the set is decided don't count
+        t.record_game(0);
+        assert_eq!(t.sets[0].wins, [1, 2]);
+    }
+}
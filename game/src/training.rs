@@ -0,0 +1,226 @@
+//this module contains training mode: player 1 against a dummy in player 2's slot, with no stock limit.
+//the dummy is an ordinary Player driven by PadInputs (through Game::send_input, like a bot), and can
+// stand still
+// block: parry every swing and projectile that comes at it
+// attack on a loop
+// replay: loop a sequence of player 1's inputs, recorded with R
+//anyone who dies comes straight back with full health, every hit is logged with its damage and
+// frame advantage, and Fighter charges and special cooldowns can be made free.
+//keys: F6 on the menu starts training; then M cycles the dummy mode, R starts/stops recording,
+// C toggles infinite charges and V toggles zero cooldowns
+use crate::*;
+use fyrox::keyboard::KeyCode;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DummyMode {
+    #[default]
+    Stand,
+    Block,
+    AttackLoop,
+    Replay,
+}
+
+impl DummyMode {
+    pub fn next(&self) -> Self {
+        match self {
+            DummyMode::Stand => DummyMode::Block,
+            DummyMode::Block => DummyMode::AttackLoop,
+            DummyMode::AttackLoop => DummyMode::Replay,
+            DummyMode::Replay => DummyMode::Stand,
+        }
+    }
+}
+
+pub struct Training {
+    pub dummy: usize,
+    pub mode: DummyMode,
+    pub infinite_charges: bool,
+    pub zero_cooldowns: bool,
+    //the tick recording started on, while recording player 1
+    recording: Option<u32>,
+    //player 1's inputs, by ticks since recording started, and how long the recording is
+    recorded: Vec<(u32, PadInput)>,
+    length: u32,
+    //how far through the recording the dummy is
+    cursor: u32,
+    //the on-screen log, newest last
+    text: Handle<UiNode>,
+    log: Vec<String>,
+}
+
+impl Training {
+    //lines of hit log kept on screen
+    const LOG_LINES: usize = 8;
+    //charges a fighter is topped back up to
+    const CHARGES: i32 = 3;
+    //how close a projectile gets before the blocking dummy parries it
+    const BLOCK_RANGE: f32 = 2.0;
+
+    pub fn new(ui: &mut UserInterface, dummy: usize) -> Self {
+        let text = create_text_with_background(ui, "", 10.0, 550.0, Color::opaque(255, 255, 255));
+        set_text_visibility(ui, text, true);
+        let training = Self {
+            dummy,
+            mode: DummyMode::default(),
+            infinite_charges: false,
+            zero_cooldowns: false,
+            recording: None,
+            recorded: Vec::new(),
+            length: 0,
+            cursor: 0,
+            text,
+            log: Vec::new(),
+        };
+        training.show(ui);
+        training
+    }
+
+    pub fn on_key(&mut self, key: KeyCode, tick: u32, ui: &mut UserInterface) {
+        match key {
+            KeyCode::KeyM => {
+                self.mode = self.mode.next();
+                self.cursor = 0;
+            },
+            KeyCode::KeyR => match self.recording.take() {
+                Some(start) => {
+                    self.length = tick - start;
+                    self.cursor = 0;
+                    self.mode = DummyMode::Replay;
+                },
+                None => {
+                    self.recording = Some(tick);
+                    self.recorded.clear();
+                },
+            },
+            KeyCode::KeyC => self.infinite_charges = !self.infinite_charges,
+            KeyCode::KeyV => self.zero_cooldowns = !self.zero_cooldowns,
+            _ => return,
+        }
+        self.show(ui);
+    }
+
+    ///called with every input sent to a player; keeps player 1's while recording
+    pub fn record(&mut self, slot: usize, tick: u32, input: PadInput) {
+        if let (Some(start), 0) = (self.recording, slot) {
+            self.recorded.push((tick - start, input));
+        }
+    }
+
+    ///what the dummy presses this tick
+    pub fn dummy_inputs(&mut self, players: &BTreeMap<usize, Handle<Node>>, graph: &Graph) -> Vec<PadInput> {
+        let dummy = match players.get(&self.dummy).and_then(|h| graph.try_get(*h)) {
+            Some(node) => node,
+            None => return Vec::new(),
+        };
+        let idle = dummy.try_get_script::<Player>().map_or(false, |p| p.state == PlayerState::Idle);
+
+        match self.mode {
+            DummyMode::Stand => Vec::new(),
+            DummyMode::AttackLoop if idle => vec![PadInput::Pressed(RightTrigger)],
+            DummyMode::Block if idle => {
+                let at = dummy.global_position();
+                let swinging = players.iter()
+                    .filter(|(slot, _)| **slot != self.dummy)
+                    .filter_map(|(_, h)| graph.try_get(*h).and_then(|n| n.try_get_script::<Player>()))
+                    .any(|p| matches!(p.state, PlayerState::Attacking(_)));
+                let incoming = graph.linear_iter()
+                    .filter(|n| n.try_get_script::<Projectile>().map_or(false, |p| !p.hit))
+                    .any(|n| (n.global_position() - at).norm() < Self::BLOCK_RANGE);
+                if swinging || incoming {
+                    vec![PadInput::Pressed(RightThumb)]
+                } else {
+                    Vec::new()
+                }
+            },
+            DummyMode::Replay if self.length > 0 && self.recording.is_none() => {
+                let from = self.cursor;
+                self.cursor += 1;
+                let mut inputs = Vec::new();
+                //the recording loops, so the window we're playing may wrap around its end
+                for (offset, input) in &self.recorded {
+                    let mut at = *offset;
+                    while at < self.cursor {
+                        if at >= from {
+                            inputs.push(*input);
+                        }
+                        at += self.length;
+                    }
+                }
+                self.cursor %= self.length;
+                inputs
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    ///brings the dead straight back and applies the toggles; called once per frame
+    pub fn update(&self, graph: &mut Graph, players: &BTreeMap<usize, Handle<Node>>) {
+        for handle in players.values() {
+            if let Some(player) = graph.try_get_mut(*handle).and_then(|n| n.try_get_script_mut::<Player>()) {
+                //skip the respawn timer; respawning is what resets health. this only moves the
+                // death countdown along, so it's an advance, and the respawn itself goes through transition()
+                if let PlayerState::Dead(frame) = player.state {
+                    if frame > 1 {
+                        player.advance(PlayerState::Dead(1));
+                    }
+                }
+                if self.infinite_charges && matches!(player.class, Class::Fighter) {
+                    player.charges = player.charges.max(Self::CHARGES);
+                }
+                if self.zero_cooldowns {
+                    player.cooldown = player.cooldown.max(Class::CCOOL + 1);
+                }
+            }
+        }
+    }
+
+    ///logs hits and parries
+    pub fn on_event(&mut self, event: &GameEvent, graph: &Graph, players: &BTreeMap<usize, Handle<Node>>, ui: &mut UserInterface) {
+        let num = |h: &Handle<Node>| players.iter().find(|(_, p)| *p == h).map_or(0, |(slot, _)| slot + 1);
+        let line = match event {
+            GameEvent::PlayerDamaged{player, attacker, damage, ..} => {
+                let advantage = match frame_advantage(graph, *attacker, *player) {
+                    Some(frames) => format!("{frames:+}"),
+                    None => "-".to_string(),
+                };
+                format!("P{} hit P{} for {damage}, advantage {advantage}", num(attacker), num(player))
+            },
+            GameEvent::ParrySucceeded{player, attacker, ..} => format!("P{} parried P{}", num(player), num(attacker)),
+            _ => return,
+        };
+        self.log.push(line);
+        if self.log.len() > Self::LOG_LINES {
+            self.log.remove(0);
+        }
+        self.show(ui);
+    }
+
+    fn show(&self, ui: &mut UserInterface) {
+        let on = |b: bool| if b {"on"} else {"off"};
+        let mut text = format!("TRAINING  dummy: {:?} (M)  {}\ninfinite charges: {} (C)  zero cooldowns: {} (V)\n",
+            self.mode,
+            if self.recording.is_some() {"recording... (R to stop)"} else {"R to record"},
+            on(self.infinite_charges), on(self.zero_cooldowns));
+        for line in &self.log {
+            text += &format!("\n{line}");
+        }
+        ui.send_message(TextMessage::text(self.text, MessageDirection::ToWidget, text));
+    }
+}
+
+///frames the attacker can act before the victim: the victim's hitstun left minus the attacker's
+/// recovery left. None if either isn't in the middle of an attack or hitstun
+pub fn frame_advantage(graph: &Graph, attacker: Handle<Node>, victim: Handle<Node>) -> Option<i32> {
+    let script = |h: Handle<Node>| graph.try_get(h).and_then(|n| n.try_get_script::<Player>());
+    let attacker = script(attacker)?;
+    let victim = script(victim)?;
+    let attack_left = match attacker.state {
+        PlayerState::Attacking(frame) => attacker.class.behavior().melee().frames.total() - frame,
+        _ => 0,
+    };
+    let stun_left = match victim.state {
+        PlayerState::Hit(frame) => Class::HITDUR - frame,
+        _ => return None,
+    };
+    Some(stun_left - attack_left)
+}
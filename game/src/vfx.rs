@@ -0,0 +1,169 @@
+//this module contains the particle effects. scripts call the spawn_* functions below
+// instead of building particle systems themselves:
+// spawn_impact: a weapon connecting with a player
+// spawn_parry_sparks: a parry catching an attack
+// spawn_death_burst: a player dying, in their team colour
+// spawn_charge_dust: the barbarian's charge kicking up dust
+// attach_trail: the trail behind a projectile
+use crate::*;
+use fyrox::{
+    core::color_gradient::{ColorGradient, GradientPoint},
+    scene::particle_system::{
+        ParticleSystemBuilder,
+        emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder},
+    },
+};
+use std::ops::Range;
+
+///the description of a reusable emitter
+#[derive(Debug, Clone)]
+pub struct Burst {
+    pub max_particles: u32,
+    //particles spawned per second
+    pub spawn_rate: u32,
+    pub radius: f32,
+    pub size: Range<f32>,
+    //how much the size changes per second; negative shrinks
+    pub shrink: Range<f32>,
+    pub speed: Range<f32>,
+    pub lifetime: Range<f32>,
+    pub color: Color,
+    //keep spawning forever instead of a one-off burst
+    pub continuous: bool,
+}
+
+impl Burst {
+    pub fn impact() -> Self {
+        Self {
+            max_particles: 20,
+            spawn_rate: 400,
+            radius: 0.05,
+            size: 0.04..0.08,
+            shrink: -0.15..-0.1,
+            speed: -0.08..0.08,
+            lifetime: 0.15..0.3,
+            color: Color::opaque(255, 240, 200),
+            continuous: false,
+        }
+    }
+
+    pub fn parry_sparks() -> Self {
+        Self {
+            max_particles: 30,
+            spawn_rate: 600,
+            radius: 0.05,
+            size: 0.03..0.05,
+            shrink: -0.1..-0.05,
+            speed: -0.12..0.12,
+            lifetime: 0.1..0.35,
+            color: Color::opaque(120, 200, 255),
+            continuous: false,
+        }
+    }
+
+    pub fn death(color: Color) -> Self {
+        Self {
+            max_particles: 60,
+            spawn_rate: 600,
+            radius: 0.2,
+            size: 0.06..0.12,
+            shrink: -0.1..-0.05,
+            speed: -0.1..0.1,
+            lifetime: 0.4..0.9,
+            color,
+            continuous: false,
+        }
+    }
+
+    pub fn charge_dust() -> Self {
+        Self {
+            max_particles: 8,
+            spawn_rate: 120,
+            radius: 0.2,
+            size: 0.05..0.1,
+            shrink: -0.1..-0.05,
+            speed: -0.02..0.02,
+            lifetime: 0.2..0.4,
+            color: Color::opaque(170, 150, 120),
+            continuous: false,
+        }
+    }
+
+    pub fn trail() -> Self {
+        Self {
+            max_particles: 40,
+            spawn_rate: 60,
+            radius: 0.05,
+            size: 0.04..0.07,
+            shrink: -0.1..-0.08,
+            speed: -0.005..0.005,
+            lifetime: 0.2..0.4,
+            color: Color::opaque(190, 140, 255),
+            continuous: true,
+        }
+    }
+
+    //how long a one-off burst needs to stay in the scene for all its particles to die
+    fn duration(&self) -> f32 {
+        self.lifetime.end + self.max_particles as f32 / self.spawn_rate as f32
+    }
+
+    ///builds the particle system; one-off bursts remove themselves once they've played
+    pub fn build(&self, graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+        let mut base = BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build()
+        );
+        if !self.continuous {
+            base = base.with_lifetime(self.duration());
+        }
+
+        //fade out over the particle's life
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(0.0, self.color));
+        gradient.add_point(GradientPoint::new(1.0, Color::from_rgba(self.color.r, self.color.g, self.color.b, 0)));
+
+        ParticleSystemBuilder::new(base)
+            .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
+            .with_color_over_lifetime_gradient(gradient)
+            .with_emitters(vec![SphereEmitterBuilder::new(
+                BaseEmitterBuilder::new()
+                    .with_max_particles(self.max_particles)
+                    .with_spawn_rate(self.spawn_rate)
+                    .with_size_range(self.size.clone())
+                    .with_size_modifier_range(self.shrink.clone())
+                    .with_x_velocity_range(self.speed.clone())
+                    .with_y_velocity_range(self.speed.clone())
+                    .with_z_velocity_range(0.0..0.0)
+                    .with_lifetime_range(self.lifetime.clone())
+                    .resurrect_particles(self.continuous),
+            )
+            .with_radius(self.radius)
+            .build()])
+            .build(graph)
+    }
+}
+
+pub fn spawn_impact(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+    Burst::impact().build(graph, position)
+}
+
+pub fn spawn_parry_sparks(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+    Burst::parry_sparks().build(graph, position)
+}
+
+pub fn spawn_death_burst(graph: &mut Graph, position: Vector3<f32>, color: Color) -> Handle<Node> {
+    Burst::death(color).build(graph, position)
+}
+
+pub fn spawn_charge_dust(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+    Burst::charge_dust().build(graph, position)
+}
+
+///attaches a continuous trail to a node (e.g. a projectile); it goes away with the node
+pub fn attach_trail(graph: &mut Graph, node: Handle<Node>) -> Handle<Node> {
+    let trail = Burst::trail().build(graph, Vector3::new(0.0, 0.0, 0.0));
+    graph.link_nodes(trail, node);
+    trail
+}
@@ -6,6 +6,10 @@ use fyrox::scene::transform::Transform;
 pub struct Weapon {
     pub player: Handle<Node>,
     pub class: Class,
+    //everything this swing has already hit, so each target is only hit once per attack
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub hit_targets: Vec<Handle<Node>>,
 }
 
 impl_component_provider!(Weapon);
@@ -28,11 +32,7 @@ impl ScriptTrait for Weapon {
 
         //setup the correct positioning and visibility of the weapon:
 
-        let offset = match self.class {
-            Class::Barbarian => 1.0,
-            Class::Fighter => 1.0,
-            _ => 0.75,
-        };
+        let offset = self.class.behavior().weapon_offset();
 
         context.scene.graph[context.handle.clone()].set_visibility(false);
         //change the local position of the weapon
@@ -57,15 +57,8 @@ impl ScriptTrait for Weapon {
     }
 
     fn on_update(&mut self, ctx: &mut ScriptContext) {
-        //get the player state
-        let mut state = PlayerState::Idle;
-        //get the player state
-        if let Some(script) = ctx.scene.graph[self.player].try_get_script::<Player>() {
-            state = script.state.clone();
-        }
-        match state {
-            PlayerState::Attacking(frame) => {self.cont_attack(frame, ctx)},
-            _ => (),
+        if sim_tick(ctx.plugins) {
+            self.tick(ctx);
         }
     }
 
@@ -82,7 +75,7 @@ impl ScriptTrait for Weapon {
                 Message::Start_Parry{} => self.start_parry(ctx),
                 Message::Hit{sender, .. } => {
                     if let Some(script) = ctx.scene.graph[self.player].try_get_script::<Player>(){
-                        if script.state == PlayerState::Parry(1){
+                        if matches!(script.state, PlayerState::Parry(_)) {
                             self.takehit(sender.clone(), ctx)
                         }
                     }
@@ -98,6 +91,19 @@ impl ScriptTrait for Weapon {
 }
 
 impl Weapon {
+    ///one tick of the simulation clock: follows the player's attack
+    pub fn tick(&mut self, ctx: &mut ScriptContext) {
+        //get the player state
+        let mut state = PlayerState::Idle;
+        //get the player state
+        if let Some(script) = ctx.scene.graph[self.player].try_get_script::<Player>() {
+            state = script.state.clone();
+        }
+        match state {
+            PlayerState::Attacking(frame) => {self.cont_attack(frame, ctx)},
+            _ => (),
+        }
+    }
 
     ///restores a weapon to its default position and settings
     pub fn restore_weapon(&self, ctx: &mut ScriptContext) {
@@ -141,13 +147,16 @@ impl Weapon {
 
     ///starts a melee attack
     /// called when player messages weapon that a valid attack can be made
-    pub fn start_melee_attack(&self, ctx: &mut ScriptMessageContext) {
+    pub fn start_melee_attack(&mut self, ctx: &mut ScriptMessageContext) {
+        self.hit_targets.clear();
         if let Some(weapon) = ctx.scene.graph[ctx.handle].cast_mut::<RigidBody>(){
             weapon.set_visibility(true);
         }
+        let x = ctx.scene.graph[ctx.handle].global_position().x;
+        play_sfx(ctx.plugins, self.class.behavior().sounds().swing, x);
     }
 
-    pub fn cont_attack(&self, frame: i32, ctx: &mut ScriptContext) {
+    pub fn cont_attack(&mut self, frame: i32, ctx: &mut ScriptContext) {
 
         //this doesn't work, maybe later find a good way to boost barbarian attack damage out of a charge
         // let barbdam = match script.state {
@@ -160,15 +169,11 @@ impl Weapon {
         if !ctx.scene.graph[ctx.handle].visibility() {return;}
         
         //match for attack constants
-        let (interval, lag, spd, dam, knock) = match self.class {
-            Class::Barbarian => (Class::BARBINT, Class::BARBLAG, Class::BARBWEPSPD, Class::BARBDAM, Class::BARBKNOCK),
-            Class::Rogue => (Class::ROGINT, Class::ROGLAG, Class::ROGWEPSPD, Class::ROGDAM, Class::ROGKNOCK),
-            Class::Wizard => (Class::WIZINT, Class::WIZLAG, Class::WIZWEPSPD, Class::WIZDAM, Class::WIZKNOCK),
-            Class::Fighter => (Class::FIGINT, Class::FIGLAG, Class::FIGWEPSPD, Class::FIGDAM, Class::FIGKNOCK),
-        };
-
-        //while in the attack
-        if frame <= interval {
+        let MeleeStats{frames, swing_speed: spd, damage: dam, knockback: knock} = self.class.behavior().melee();
+        let phase = frames.phase(frame);
+
+        //while in the swing
+        if matches!(phase, AttackPhase::Startup | AttackPhase::Active) {
             //continue the swing
             if let Some(weapon) = ctx.scene.graph[ctx.handle].cast_mut::<RigidBody>(){
                 //rotate the weapon equal to the weapon speed constant
@@ -176,8 +181,13 @@ impl Weapon {
                 weapon.local_transform_mut().set_rotation(currotation.append_axisangle_linearized(
                     &(&Vector3::z() * spd)));
             }
-            //check for hits
-            //let mut hit = (false, self.player);
+        }
+
+        //only active frames can hit
+        if phase == AttackPhase::Active {
+            //collect everything the weapon is touching first, so the graph isn't borrowed
+            // while we react to the hits
+            let mut touching = Vec::new();
             //find the collider of the weapon
             if let Some((collider_handle, colnode)) = ctx.scene.graph.find(ctx.handle, &mut |c| c.is_collider2d()) {
                 for i in colnode.as_collider2d().intersects(&ctx.scene.graph.physics2d) {
@@ -189,48 +199,52 @@ impl Weapon {
                     } else {
                         ctx.scene.graph[i.collider1].parent()
                     };
-                    if other_collider_parent == self.player {
-                        //stop hitting yourself
-                        return;
-                    }
-                    let parent_node = &ctx.scene.graph[other_collider_parent.clone()];
-                    if parent_node.script().is_some() {
-                        if matches!(self.class, Class::Fighter) {
-                            if let Some(script) = ctx.scene.graph[other_collider_parent].try_get_script::<Player>() {
-                                //if the player hit is not dead or invincible, 
-                                //tell fighters to increase their charge on a sucessful hit
-                                if !((script.state == PlayerState::Dead(1)) | (script.iframes > 0)){
-                                    ctx.message_sender.send_to_target(self.player,
-                                        Message::Charges{i: 1}
-                                    );
-                                }
-                            }
-                        }
+                    touching.push(other_collider_parent);
+                }
+            }
 
-                        let mut knockvec = Vector3::new(1.0,1.0, 1.0);
-                        //get the knockback vector
-                        if let Some(script) = ctx.scene.graph[self.player].try_get_script::<Player>(){
-                            knockvec = script.facing.clone();
-                            knockvec.set_magnitude(knock);
-                        }
+            for other_collider_parent in touching {
+                if other_collider_parent == self.player {
+                    //stop hitting yourself
+                    continue;
+                }
+                if self.hit_targets.contains(&other_collider_parent) {
+                    //already hit this one during this swing
+                    continue;
+                }
+                if ctx.scene.graph[other_collider_parent].script().is_none() {
+                    continue;
+                }
 
+                let mut landed = false;
+                if let Some(script) = ctx.scene.graph[other_collider_parent].try_get_script::<Player>() {
+                    //only counts as a hit if the player hit is not dead or invincible
+                    landed = !(script.state.is_dead() | (script.iframes > 0));
+                }
+                if landed {
+                    let position = ctx.scene.graph[other_collider_parent].global_position();
+                    spawn_impact(&mut ctx.scene.graph, position);
+                    self.class.behavior().on_hit(self, other_collider_parent, ctx);
+                }
 
-                        ctx.message_sender.send_to_target(other_collider_parent,
-                            Message::Hit{
-                                damage: dam,
-                                knockback: knockvec,
-                                sender: ctx.handle,
-                            }
-                        )  
-                    }
+                let mut knockvec = Vector3::new(1.0,1.0, 1.0);
+                //get the knockback vector
+                if let Some(script) = ctx.scene.graph[self.player].try_get_script::<Player>(){
+                    knockvec = script.facing.clone();
+                    knockvec.set_magnitude(knock);
                 }
-            }
 
-        } else if frame < interval + lag {
-            //if we're in end lag, don't touch the weapon, just advance the frame
-        } else {
-            //attack is over
+                ctx.message_sender.send_to_target(other_collider_parent,
+                    Message::Hit{
+                        damage: dam,
+                        knockback: knockvec,
+                        sender: ctx.handle,
+                    }
+                );
+                self.hit_targets.push(other_collider_parent);
+            }
         }
+        //during recovery, don't touch the weapon; the player ends the attack
     }
 
     pub fn start_parry(&mut self, ctx: &mut ScriptMessageContext) {
@@ -252,8 +266,15 @@ impl Weapon {
         ctx.message_sender.send_to_target(sender, 
             Message::Attack{s: false}
         );
-        println!("sent");
-        // add a rogue charge on a successful parry
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        spawn_parry_sparks(&mut ctx.scene.graph, position);
+        let attacker = attacker_of(&ctx.scene.graph, sender);
+        publish(ctx.plugins, GameEvent::ParrySucceeded{player: self.player, attacker, position});
+
+        //let our player react to the successful parry
+        ctx.message_sender.send_to_target(self.player,
+            Message::Parried{}
+        );
 
     }
 
@@ -0,0 +1,36 @@
+//the Wizard: slow, fragile, and does no melee damage, but can fire projectiles
+use crate::*;
+use crate::behavior::{ClassBehavior, MeleeStats};
+use fyrox::scene::dim2::collider::CuboidShape;
+
+pub struct WizardBehavior;
+
+impl ClassBehavior for WizardBehavior {
+    fn health(&self) -> u32 { Class::WIZHEALTH }
+    fn speed(&self) -> f32 { Class::WIZSPD }
+    fn sprite(&self) -> &'static str { "data/White_star.png" }
+    fn weapon_shape(&self) -> CuboidShape { Class::WIZWEP }
+    fn special_cooldown(&self) -> Option<i32> { Some(Class::RCOOL) }
+
+    fn melee(&self) -> MeleeStats {
+        MeleeStats {
+            frames: Class::WIZFRAMES,
+            swing_speed: Class::WIZWEPSPD,
+            damage: Class::WIZDAM,
+            knockback: Class::WIZKNOCK,
+        }
+    }
+
+    fn sounds(&self) -> SoundSet {
+        SoundSet {
+            swing: "data/sounds/wizard_swing.ogg",
+            fire: "data/sounds/wizard_fire.ogg",
+            ..SoundSet::default()
+        }
+    }
+
+    ///fires a projectile whenever the cooldown allows
+    fn special(&self, player: &mut Player, ctx: &mut ScriptMessageContext) {
+        player.fire_projectile(ctx);
+    }
+}
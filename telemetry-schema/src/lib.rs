@@ -0,0 +1,58 @@
+//! The format of the game's telemetry files: the game (game/src/telemetry.rs) writes these lines
+//! and the analytics tool reads them back. It only needs serde, so the analytics tool can read
+//! telemetry without building the engine.
+//! The game uses MatchRules, PlayerStats and TICK_RATE from here as its own, so what it writes
+//! can't drift away from what gets read.
+use serde::{Deserialize, Serialize};
+
+/// Simulation ticks per second; the `tick` of every telemetry line counts these.
+pub const TICK_RATE: i32 = 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MatchRules {
+    //lives per player; None means players respawn forever
+    pub stocks: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub damage_dealt: u32,
+    pub damage_taken: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    pub parries: u32,
+    pub projectiles: u32,
+}
+
+/// A player as telemetry sees them; scene handles mean nothing outside the game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub slot: usize,
+    //the name of their class, e.g. "Barbarian"
+    pub class: String,
+    pub bot: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TelemetryEvent {
+    MatchStarted{ rules: MatchRules, players: Vec<PlayerInfo> },
+    Hit{ attacker: Option<PlayerInfo>, victim: PlayerInfo, damage: u32, position: [f32; 2] },
+    ParryStarted{ player: PlayerInfo },
+    Parry{ player: PlayerInfo, attacker: Option<PlayerInfo>, position: [f32; 2] },
+    Death{ player: PlayerInfo, killer: Option<PlayerInfo>, position: [f32; 2] },
+    Respawn{ player: PlayerInfo, position: [f32; 2] },
+    ProjectileFired{ player: PlayerInfo },
+    //`completed` is false if the game closed before the match was decided
+    MatchEnded{ winner: Option<PlayerInfo>, completed: bool, players: Vec<(PlayerInfo, PlayerStats)> },
+}
+
+/// One line of a telemetry file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryLine {
+    pub match_id: u64,
+    pub tick: u32,
+    pub time: u64,
+    #[serde(flatten)]
+    pub event: TelemetryEvent,
+}